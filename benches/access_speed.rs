@@ -0,0 +1,45 @@
+use bitset::bitset::BitSet;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const SIZE: usize = 10_000;
+
+fn checked_access(bitset: &mut BitSet) -> usize {
+    let mut res = 0;
+    for i in 0..SIZE {
+        bitset.set(i, i % 3 == 0);
+    }
+    for i in 0..SIZE {
+        if bitset.get(i) {
+            res += 1;
+        }
+    }
+    res
+}
+
+fn unchecked_access(bitset: &mut BitSet) -> usize {
+    let mut res = 0;
+    for i in 0..SIZE {
+        // the positions are always lower than the size
+        unsafe { bitset.set_unchecked(i, i % 3 == 0) };
+    }
+    for i in 0..SIZE {
+        if unsafe { bitset.get_unchecked(i) } {
+            res += 1;
+        }
+    }
+    res
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut bitset = BitSet::new(SIZE);
+
+    let mut c = c.benchmark_group("Access");
+    c.bench_function("checked_access", |b| b.iter(|| checked_access(&mut bitset)));
+    c.bench_function("unchecked_access", |b| {
+        b.iter(|| unchecked_access(&mut bitset))
+    });
+    c.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -6,11 +6,11 @@ fn simple_bits_inline() -> u64 {
 
     // set all bits one by one
     for i in 0..63 {
-        value = value | (1 << i);
+        value |= 1 << i;
     }
     // reset all bits one by one
     for i in 0..63 {
-        value = value & !(1 << i);
+        value &= !(1 << i);
     }
     value
 }
@@ -20,11 +20,11 @@ fn simple_bits() -> u64 {
 
     // set all bits one by one
     for i in 0..63 {
-        value = value | (1 << i);
+        value |= 1 << i;
     }
     // reset all bits one by one
     for i in 0..63 {
-        value = value & !(1 << i);
+        value &= !(1 << i);
     }
     value
 }
@@ -33,11 +33,11 @@ fn array_bits_inline() -> u64 {
     let mut value: [u64; 1] = [0];
     // set all bits one by one
     for i in 0..63 {
-        value[0] = value[0] | (1 << i);
+        value[0] |= 1 << i;
     }
     // reset all bits one by one
     for i in 0..63 {
-        value[0] = value[0] & !(1 << i);
+        value[0] &= !(1 << i);
     }
     value[0]
 }
@@ -46,11 +46,11 @@ fn array_bits() -> u64 {
     let mut value: [u64; 1] = [0];
     // set all bits one by one
     for i in 0..63 {
-        value[0] = value[0] | (1 << i);
+        value[0] |= 1 << i;
     }
     // reset all bits one by one
     for i in 0..63 {
-        value[0] = value[0] & !(1 << i);
+        value[0] &= !(1 << i);
     }
     value[0]
 }
@@ -59,11 +59,11 @@ fn vector_bits_inline() -> u64 {
     let mut value: Vec<u64> = vec![0];
     // set all bits one by one
     for i in 0..63 {
-        value[0] = value[0] | (1 << i);
+        value[0] |= 1 << i;
     }
     // reset all bits one by one
     for i in 0..63 {
-        value[0] = value[0] & !(1 << i);
+        value[0] &= !(1 << i);
     }
     value[0]
 }
@@ -72,23 +72,23 @@ fn vector_bits() -> u64 {
     let mut value: Vec<u64> = vec![0];
     // set all bits one by one
     for i in 0..63 {
-        value[0] = value[0] | (1 << i);
+        value[0] |= 1 << i;
     }
     // reset all bits one by one
     for i in 0..63 {
-        value[0] = value[0] & !(1 << i);
+        value[0] &= !(1 << i);
     }
     value[0]
 }
 
 fn criterion_benchmark(c: &mut Criterion) {
     let mut c = c.benchmark_group("Bits");
-    c.bench_function("simple_bits", |b| b.iter(|| simple_bits()));
-    c.bench_function("array_bits", |b| b.iter(|| array_bits()));
-    c.bench_function("vector_bits", |b| b.iter(|| vector_bits()));
-    c.bench_function("simple_bits_inline", |b| b.iter(|| simple_bits_inline()));
-    c.bench_function("array_bits_inline", |b| b.iter(|| array_bits_inline()));
-    c.bench_function("vector_bits_inline", |b| b.iter(|| vector_bits_inline()));
+    c.bench_function("simple_bits", |b| b.iter(simple_bits));
+    c.bench_function("array_bits", |b| b.iter(array_bits));
+    c.bench_function("vector_bits", |b| b.iter(vector_bits));
+    c.bench_function("simple_bits_inline", |b| b.iter(simple_bits_inline));
+    c.bench_function("array_bits_inline", |b| b.iter(array_bits_inline));
+    c.bench_function("vector_bits_inline", |b| b.iter(vector_bits_inline));
     c.finish();
 }
 
@@ -0,0 +1,35 @@
+use bitset::bitset::BitSet;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const BLOCKS: usize = 1_000_000;
+
+fn make_bitset() -> BitSet {
+    let size = BLOCKS * usize::BITS as usize;
+    let mut bitset = BitSet::new(size);
+    for i in (0..size).step_by(3) {
+        bitset.set(i, true);
+    }
+    bitset
+}
+
+fn naive_count(blocks: &[usize]) -> usize {
+    let mut res = 0;
+    for block in blocks {
+        res += block.count_ones() as usize;
+    }
+    res
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let bitset = make_bitset();
+    // the same data for both, so the numbers can be compared
+    let blocks = bitset.raw_blocks();
+
+    let mut c = c.benchmark_group("Count");
+    c.bench_function("bitset_count", |b| b.iter(|| bitset.count()));
+    c.bench_function("naive_count", |b| b.iter(|| naive_count(blocks)));
+    c.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
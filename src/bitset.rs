@@ -5,15 +5,19 @@
 //! Use TryFrom<> inst
 
 extern crate num;
-use core::ops::{BitAnd, BitAndAssign};
+use core::ops::{
+    BitAnd, BitAndAssign, BitOr, BitXor, Index, Range, Shl, ShlAssign, Shr, ShrAssign, Sub,
+};
 // use num::traits::Unsigned;
-use std::cmp::max;
 use std::cmp::PartialEq;
+use std::cmp::{max, min};
 use std::convert::From;
 use std::convert::TryFrom;
 // use std::convert::TryInto;
 // use std::default::Default;
 use std::fmt;
+use std::io::{self, Read, Write};
+use std::iter::FromIterator;
 // use std::fmt::Display;
 use std::mem::size_of;
 // use std::ops::Add;
@@ -31,8 +35,12 @@ struct BitPosition {
 }
 
 #[derive(Debug, PartialEq)] // Allow the use of "{:?}" format specifier
-enum BitSetError {
+pub enum BitSetError {
     EnlargeError { from: usize, to: usize },
+    RadixError { radix: u32 },
+    DigitError { digit: char, radix: u32 },
+    EmptyStringError,
+    OverflowError { size: usize },
 }
 
 // Allow the use of "{}" format specifier
@@ -44,10 +52,40 @@ impl fmt::Display for BitSetError {
                 "Cannot enlarge the bitset to a smaller size {} -> {}.",
                 from, to
             ),
+            BitSetError::RadixError { radix } => {
+                write!(f, "Radix {} is not supported, use 2, 8 or 16.", radix)
+            }
+            BitSetError::DigitError { digit, radix } => {
+                write!(f, "Invalid digit '{}' for radix {}.", digit, radix)
+            }
+            BitSetError::EmptyStringError => write!(f, "Cannot parse an empty string."),
+            BitSetError::OverflowError { size } => {
+                write!(f, "The value doesn't fit in {} bits.", size)
+            }
         }
     }
 }
-#[derive(Debug)]
+
+/// The error returned by the operations on two bitsets, which have different sizes.
+#[derive(Debug, PartialEq)]
+pub struct SizeMismatch {
+    pub left: usize,
+    pub right: usize,
+}
+
+impl fmt::Display for SizeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "BitSets have different sizes: {} and {}.",
+            self.left, self.right
+        )
+    }
+}
+
+impl std::error::Error for SizeMismatch {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BitSet {
     /// list of blocks with data
     blocks: Vec<usize>,
@@ -71,17 +109,17 @@ impl BitSet {
 
     /// Returns the size of one block in bits.
     fn block_size() -> usize {
-        size_of::<usize>() * 8
+        usize::BITS as usize
     }
 
     /// Panic if the passed position argument is outside the range [0; self.size)
     fn assert_position(&self, position: usize) {
         if position >= self.size {
-            panic!(format!(
+            panic!(
                 "Bit position [{}] is outside available range: [0, {}]",
                 position,
                 self.size - 1
-            ));
+            );
         }
     }
 
@@ -97,6 +135,227 @@ impl BitSet {
     fn make_bitmask(position: usize) -> usize {
         1 << position
     }
+
+    /// Panic if the passed range [from; to) is not inside the range [0; self.size]
+    fn assert_range(&self, from: usize, to: usize) {
+        if from > to || to > self.size {
+            panic!(
+                "Bit range [{}, {}) is outside available range: [0, {}]",
+                from,
+                to,
+                self.size - 1
+            );
+        }
+    }
+
+    /// Panic if the other bitset has a different size.
+    fn assert_same_size(&self, other: &Self) {
+        if let Err(error) = self.check_same_size(other) {
+            panic!("{}", error);
+        }
+    }
+
+    /// Returns the error if the other bitset has a different size.
+    fn check_same_size(&self, other: &Self) -> Result<(), SizeMismatch> {
+        if self.size != other.size {
+            return Err(SizeMismatch {
+                left: self.size,
+                right: other.size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Replaces each block with the result of the operation on it
+    /// and the block of the other bitset.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    fn combine<F: Fn(usize, usize) -> usize>(&mut self, other: &Self, op: F) {
+        self.assert_same_size(other);
+
+        for (block, other_block) in self.blocks.iter_mut().zip(other.blocks.iter()) {
+            *block = op(*block, *other_block);
+        }
+        self.clear_unused_bits();
+    }
+
+    /// Returns the number of bits set to true in the result of the operation
+    /// on the blocks of both bitsets, without creating the result bitset.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    fn count_combined<F: Fn(usize, usize) -> usize>(&self, other: &Self, op: F) -> usize {
+        self.assert_same_size(other);
+
+        let last_block_number = self.blocks.len() - 1;
+        let mut res = 0;
+        for (block_number, (left, right)) in self.blocks.iter().zip(other.blocks.iter()).enumerate()
+        {
+            let mut block = op(*left, *right);
+            if block_number == last_block_number {
+                block &= self.last_block_bitmask();
+            }
+            res += block.count_ones() as usize;
+        }
+        res
+    }
+
+    /// Returns a new bitset with the result of the operation on each pair of the blocks.
+    ///
+    /// The bitsets can have different sizes, the missing blocks of the smaller one
+    /// are treated as zeros, and the result has the size of the larger one.
+    fn combine_extended<F: Fn(usize, usize) -> usize>(&self, other: &Self, op: F) -> BitSet {
+        let mut blocks = Vec::with_capacity(max(self.blocks.len(), other.blocks.len()));
+        for item in self.blocks.iter().zip_longest(other.blocks.iter()) {
+            blocks.push(match item {
+                Both(l, r) => op(*l, *r),
+                Left(l) => op(*l, 0),
+                Right(r) => op(0, *r),
+            });
+        }
+        let mut output = BitSet {
+            blocks,
+            size: max(self.size, other.size),
+        };
+        output.clear_unused_bits();
+        output
+    }
+
+    /// Does the operation on each pair of the blocks, storing the result in this bitset.
+    ///
+    /// The same as `combine_extended()`, but this bitset is enlarged in place when needed,
+    /// so its blocks are reused.
+    fn combine_extend<F: Fn(usize, usize) -> usize>(&mut self, other: &Self, op: F) {
+        if other.size > self.size {
+            // the bits above the size are zeros already, so the new bits are false
+            self.blocks.resize(other.blocks.len(), 0);
+            self.size = other.size;
+        }
+        for (block_number, block) in self.blocks.iter_mut().enumerate() {
+            let other_block = other.blocks.get(block_number).copied().unwrap_or(0);
+            *block = op(*block, other_block);
+        }
+        self.clear_unused_bits();
+    }
+
+    /// Calculates the bitmask with the bits [from; to) set.
+    /// The `to` must be in the range (from; block_size].
+    fn make_range_bitmask(from: usize, to: usize) -> usize {
+        (usize::MAX >> (Self::block_size() - to)) & (usize::MAX << from)
+    }
+
+    /// Returns the block numbers and the bitmasks covering the bits [from; to).
+    fn get_range_bitmasks(from: usize, to: usize) -> impl Iterator<Item = (usize, usize)> {
+        let block_size = Self::block_size();
+        let first_block = from / block_size;
+        let blocks = if from < to {
+            first_block..(to - 1) / block_size + 1
+        } else {
+            0..0
+        };
+        blocks.map(move |block_number| {
+            let start = max(from, block_number * block_size) - block_number * block_size;
+            let end = min(to, (block_number + 1) * block_size) - block_number * block_size;
+            (block_number, Self::make_range_bitmask(start, end))
+        })
+    }
+
+    /// Shifts the bits of all the blocks towards the lower positions, ignoring the size.
+    /// The `n` must be lower than the number of bits in all the blocks.
+    fn shift_blocks_right(&mut self, n: usize) {
+        let block_size = Self::block_size();
+        let blocks_shift = n / block_size;
+        let bits_shift = n % block_size;
+        let blocks_number = self.blocks.len();
+
+        // going from the bottom, so the source blocks are not overwritten before reading
+        for i in 0..blocks_number {
+            let mut block = 0;
+            let source = i + blocks_shift;
+            if source < blocks_number {
+                block = self.blocks[source] >> bits_shift;
+                if bits_shift != 0 && source + 1 < blocks_number {
+                    block |= self.blocks[source + 1] << (block_size - bits_shift);
+                }
+            }
+            self.blocks[i] = block;
+        }
+    }
+
+    /// Returns the number of the neighbouring bits with the value, starting at the position.
+    ///
+    /// The bits are checked block by block, not one by one.
+    fn run_length(&self, position: usize, value: bool) -> usize {
+        let mut end = position;
+        while end < self.size {
+            let bit_position = Self::get_bit_position(end);
+            let block = self.blocks[bit_position.block_number];
+            let block = if value { block } else { !block };
+            let bits = (block >> bit_position.block_position).trailing_ones() as usize;
+            end += bits;
+            if bit_position.block_position + bits < Self::block_size() {
+                break;
+            }
+        }
+        // the inverted bits outside the size are true
+        min(end, self.size) - position
+    }
+
+    /// Returns the `n` bits starting at the position, moved to the lowest bits.
+    /// The `n` must be in the range (0; block_size], and the bits must be inside the blocks.
+    fn get_bits(&self, position: usize, n: usize) -> usize {
+        let block_size = Self::block_size();
+        let bit_position = Self::get_bit_position(position);
+        let mut bits = self.blocks[bit_position.block_number] >> bit_position.block_position;
+        if bit_position.block_position + n > block_size {
+            bits |= self.blocks[bit_position.block_number + 1]
+                << (block_size - bit_position.block_position);
+        }
+        bits & Self::make_range_bitmask(0, n)
+    }
+
+    /// Replaces the `n` bits starting at the position with the lowest bits of the value.
+    /// The `n` must be in the range (0; block_size], and the bits must be inside the blocks.
+    fn set_bits(&mut self, position: usize, n: usize, bits: usize) {
+        let block_size = Self::block_size();
+        let bit_position = Self::get_bit_position(position);
+        for (block_number, bitmask) in Self::get_range_bitmasks(position, position + n) {
+            let value = if block_number == bit_position.block_number {
+                bits << bit_position.block_position
+            } else {
+                bits >> (block_size - bit_position.block_position)
+            };
+            let block = &mut self.blocks[block_number];
+            *block = (*block & !bitmask) | (value & bitmask);
+        }
+    }
+
+    /// Mixes the bits of the value with the splitmix64 finalizer.
+    fn mix(value: u64) -> u64 {
+        let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Calculates the bitmask with the bits of the last block, which are inside the size.
+    fn last_block_bitmask(&self) -> usize {
+        match self.size % Self::block_size() {
+            0 => usize::MAX,
+            used_bits => Self::make_range_bitmask(0, used_bits),
+        }
+    }
+
+    /// Sets to false all the bits in the last block, which are outside the size.
+    fn clear_unused_bits(&mut self) {
+        let bitmask = self.last_block_bitmask();
+        if let Some(block) = self.blocks.last_mut() {
+            *block &= bitmask;
+        }
+    }
 }
 
 // Constructors
@@ -109,7 +368,7 @@ impl BitSet {
     /// so a BitSet of zero length is useless, as you won't be able to
     /// do anything with that.
     ///
-    /// This is the reason why there is no `default()` or `with_capacity()` functions
+    /// This is the reason why `default()` creates a bitset with just one bit,
     /// and you should use `new()` instead.
     ///
     /// For the same reason, this function panics when you would try to create
@@ -122,13 +381,161 @@ impl BitSet {
         if size == 0 {
             panic!("Creating BitSet with zero bits is not allowed.");
         }
-        let blocks_number = Self::blocks_number(size);
-        let mut blocks = Vec::with_capacity(blocks_number);
-        for _ in 0..blocks_number {
-            blocks.push(0);
+        let blocks = vec![0; Self::blocks_number(size)];
+        BitSet { blocks, size }
+    }
+
+    /// Creates a new BitSet with the given amount of allowed bits,
+    /// with the memory reserved for at least `capacity_bits` bits.
+    ///
+    /// This way the bitset can be resized up to `capacity_bits` without reallocating.
+    ///
+    /// Panics:
+    ///    - when size=0
+    ///
+    pub fn with_capacity(size: usize, capacity_bits: usize) -> Self {
+        if size == 0 {
+            panic!("Creating BitSet with zero bits is not allowed.");
         }
+        let mut blocks = Vec::with_capacity(Self::blocks_number(max(size, capacity_bits)));
+        blocks.resize(Self::blocks_number(size), 0);
         BitSet { blocks, size }
     }
+
+    /// Creates a new BitSet with all the bits set to the value.
+    ///
+    /// Panics:
+    ///    - when size=0
+    ///
+    pub fn new_filled(size: usize, value: bool) -> Self {
+        if size == 0 {
+            panic!("Creating BitSet with zero bits is not allowed.");
+        }
+        let block = if value { usize::MAX } else { 0 };
+        let mut bitset = BitSet {
+            blocks: vec![block; Self::blocks_number(size)],
+            size,
+        };
+        bitset.clear_unused_bits();
+        bitset
+    }
+
+    /// Creates a new BitSet with the alternating bits, the bit 0 is set to `start`.
+    ///
+    /// Panics:
+    ///    - when size=0
+    ///
+    pub fn checkerboard(size: usize, start: bool) -> Self {
+        let mut bitset = BitSet::new(size);
+        // the bits 0, 2, 4...
+        let even_bits = usize::MAX / 3;
+        let pattern = if start { even_bits } else { even_bits << 1 };
+        bitset.blocks.fill(pattern);
+        bitset.clear_unused_bits();
+        bitset
+    }
+
+    /// Creates a new BitSet with the bits at the positions set to true.
+    ///
+    /// Panics:
+    ///    - when size=0
+    ///    - if any position is larger than the max bit number (which is size-1)
+    ///
+    pub fn from_indices<I: IntoIterator<Item = usize>>(size: usize, indices: I) -> Self {
+        let mut bitset = BitSet::new(size);
+        bitset.extend_from_indices(indices);
+        bitset
+    }
+
+    /// Creates a new BitSet with the bits in the range set to true.
+    ///
+    /// Panics:
+    ///    - when size=0
+    ///    - if the range start is larger than the range end
+    ///    - if the range end is larger than the size
+    ///
+    pub fn from_range(size: usize, range: Range<usize>) -> Self {
+        let mut bitset = BitSet::new(size);
+        bitset.set_all_range(range.start, range.end, true);
+        bitset
+    }
+
+    /// Creates a builder for a BitSet with the given size.
+    ///
+    /// The bits to set are recorded by the builder and checked when calling `build()`,
+    /// so e.g. `BitSet::builder(16).set(3).range(10..12).build()`.
+    ///
+    pub fn builder(size: usize) -> BitSetBuilder {
+        BitSetBuilder {
+            size,
+            positions: vec![],
+            ranges: vec![],
+        }
+    }
+
+    /// Creates a BitSet directly from the blocks, like the ones returned by `raw_blocks()`.
+    ///
+    /// # Safety
+    ///
+    /// The size must be larger than zero, the number of blocks must be exactly
+    /// the number needed for the size, and the bits outside the size must be false.
+    /// The other functions rely on that and may panic or return wrong results otherwise.
+    pub unsafe fn from_raw_parts(blocks: Vec<usize>, size: usize) -> Self {
+        let bitset = BitSet { blocks, size };
+        debug_assert!(size > 0);
+        debug_assert_eq!(bitset.blocks.len(), Self::blocks_number(size));
+        let unused_bits = !bitset.last_block_bitmask();
+        debug_assert_eq!(bitset.blocks[bitset.blocks.len() - 1] & unused_bits, 0);
+        bitset
+    }
+}
+
+/// Creates a bitset with one bit set to false, the smallest allowed one.
+///
+/// There are no zero bits bitsets, so this is mainly for the types requiring `Default`,
+/// the bitset can be resized later.
+impl Default for BitSet {
+    fn default() -> Self {
+        BitSet::new(1)
+    }
+}
+
+/// Builder for a BitSet, created with `BitSet::builder()`.
+#[derive(Debug, Clone)]
+pub struct BitSetBuilder {
+    size: usize,
+    positions: Vec<usize>,
+    ranges: Vec<Range<usize>>,
+}
+
+impl BitSetBuilder {
+    /// Marks the bit at the position to be set.
+    pub fn set(mut self, position: usize) -> Self {
+        self.positions.push(position);
+        self
+    }
+
+    /// Marks all the bits in the range to be set.
+    pub fn range(mut self, range: Range<usize>) -> Self {
+        self.ranges.push(range);
+        self
+    }
+
+    /// Creates the BitSet with all the marked bits set.
+    ///
+    /// Panics:
+    ///    - when size=0
+    ///    - if any position is larger than the max bit number (which is size-1)
+    ///    - if any range start is larger than the range end
+    ///    - if any range end is larger than the size
+    ///
+    pub fn build(self) -> BitSet {
+        let mut bitset = BitSet::from_indices(self.size, self.positions);
+        for range in self.ranges {
+            bitset.set_all_range(range.start, range.end, true);
+        }
+        bitset
+    }
 }
 
 // Basic functions
@@ -158,757 +565,6056 @@ impl BitSet {
         let bit_position = Self::get_bit_position(position);
         let bitmask = Self::make_bitmask(bit_position.block_position);
 
-        if value == true {
-            self.blocks[bit_position.block_number] =
-                self.blocks[bit_position.block_number] | bitmask;
+        if value {
+            self.blocks[bit_position.block_number] |= bitmask;
         } else {
-            self.blocks[bit_position.block_number] =
-                self.blocks[bit_position.block_number] & !bitmask;
+            self.blocks[bit_position.block_number] &= !bitmask;
         }
     }
-}
 
-// utility functions
-impl BitSet {
-    /// Returns true if all bits are set. False if any is not set.
-    fn all(&self) -> bool {
-        for block in &self.blocks {
-            if *block != usize::MAX {
-                return false;
-            }
-        }
-        return true;
+    /// Returns true if the bit at the position is set.
+    ///
+    /// Unlike `get`, this doesn't panic for a position outside the bitset,
+    /// such a bit is just not contained, so false is returned.
+    ///
+    pub fn contains_bit(&self, position: usize) -> bool {
+        position < self.size && self.get(position)
     }
 
-    /// Returns true if all none bit is set. False if all are not set.
-    fn any(&self) -> bool {
-        for block in &self.blocks {
-            if *block != 0 {
-                return true;
-            }
-        }
-        return false;
+    /// Sets the bit at the position.
+    /// Returns true if the bit was previously clear.
+    ///
+    /// Panics:
+    ///    - if the position is larger than the max bit number (which is size-1)
+    ///
+    pub fn insert(&mut self, position: usize) -> bool {
+        let was_set = self.get(position);
+        self.set(position, true);
+        !was_set
     }
 
-    /// Returns number of bits set to true.
-    fn count(&self) -> u32 {
-        let mut res = 0;
-        for block in &self.blocks {
-            res += block.count_ones();
-        }
-        res
+    /// Clears the bit at the position.
+    /// Returns true if the bit was previously set.
+    ///
+    /// Panics:
+    ///    - if the position is larger than the max bit number (which is size-1)
+    ///
+    pub fn remove(&mut self, position: usize) -> bool {
+        let was_set = self.get(position);
+        self.set(position, false);
+        was_set
     }
 
-    /// Enlarges the bitset to the required size.
+    /// Gets the bit from the position, without checking the range.
     ///
-    /// All the new bits are set to false.
+    /// # Safety
     ///
-    /// The error is returned when
+    /// The position must be lower than the size.
+    pub unsafe fn get_unchecked(&self, position: usize) -> bool {
+        let bit_position = Self::get_bit_position(position);
+        let bitmask = Self::make_bitmask(bit_position.block_position);
+
+        self.blocks.get_unchecked(bit_position.block_number) & bitmask != 0
+    }
+
+    /// Sets the bit value at the position, without checking the range.
     ///
-    fn enlarge(&mut self, new_size: usize) -> Result<(), BitSetError> {
-        if new_size <= self.size {
-            return Err(BitSetError::EnlargeError {
-                from: self.size,
-                to: new_size,
-            });
+    /// # Safety
+    ///
+    /// The position must be lower than the size.
+    pub unsafe fn set_unchecked(&mut self, position: usize, value: bool) {
+        let bit_position = Self::get_bit_position(position);
+        let bitmask = Self::make_bitmask(bit_position.block_position);
+        let block = self.blocks.get_unchecked_mut(bit_position.block_number);
+
+        if value {
+            *block |= bitmask;
+        } else {
+            *block &= !bitmask;
         }
+    }
 
-        // let's create new blocks, if needed
-        let required_new_blocks = Self::blocks_number(new_size);
-        if required_new_blocks > self.blocks.len() {
-            for _ in 1..required_new_blocks - self.blocks.len() {
-                self.blocks.push(0);
-            }
+    /// Gets the bit from the position, without panicking.
+    ///
+    /// Returns None if the position is larger than the max bit number (which is size-1).
+    ///
+    pub fn try_get(&self, position: usize) -> Option<bool> {
+        if position >= self.size {
+            return None;
         }
-        self.size = new_size;
-        return Ok(());
+        Some(self.get(position))
     }
-}
 
-macro_rules! add_from_uint_trait {
-    ($t:ty) => {
-        impl From<$t> for BitSet {
-            fn from(value: $t) -> Self {
-                // number of bytes we need in memory for the value
-                let required_size = size_of::<$t>();
-                // number of blocks needed for the values
-                let blocks_number = Self::blocks_number(required_size);
-
-                // now we need to slice the blocks in groups as every block
-                // contains a couple of bytes (depending on the machine)
-                let bytes_per_block = size_of::<usize>();
-
-                // if we need more blocks, then we need to convert the bits
-                // we store Little Endian in the list of blocks
-                let value_bytes = value.to_be_bytes();
-
-                let mut blocks: Vec<usize> = Vec::with_capacity(blocks_number);
-
-                // it is possible that we convert e.g. u16 -> usize(u64)
-                // in this case, there are only 2 bytes and the compiler is not happy about
-                // so, let's add some more bytes
-
-                println! {"value {}", value}
-                for chunk in value_bytes.chunks(bytes_per_block) {
-                    let mut block: usize = 0;
-                    for byte in chunk {
-                        block = block << 8;
-                        block = block | usize::from(*byte);
-                    }
-                    blocks.push(block);
-                }
+    /// Sets the bit value at the position, without panicking.
+    ///
+    /// Returns the previous value of the bit,
+    /// or None if the position is larger than the max bit number (which is size-1).
+    /// In the latter case the bitset is not changed.
+    ///
+    pub fn try_set(&mut self, position: usize, value: bool) -> Option<bool> {
+        let previous = self.try_get(position)?;
+        self.set(position, value);
+        Some(previous)
+    }
 
-                blocks.reverse();
+    /// Sets to true the bits at all the positions.
+    ///
+    /// Panics:
+    ///    - if any position is larger than the max bit number (which is size-1)
+    ///
+    pub fn extend_from_indices<I: IntoIterator<Item = usize>>(&mut self, indices: I) {
+        for position in indices {
+            self.set(position, true);
+        }
+    }
 
-                Self {
-                    blocks: blocks,
-                    size: size_of::<$t>() * 8,
+    /// Sets to false the bits set to true, whose positions don't satisfy the predicate.
+    ///
+    /// The predicate is called only for the positions of the bits set to true, in ascending order.
+    pub fn retain<F: FnMut(usize) -> bool>(&mut self, mut f: F) {
+        let block_size = Self::block_size();
+        for (block_number, block) in self.blocks.iter_mut().enumerate() {
+            let mut bits = *block;
+            while bits != 0 {
+                let block_position = bits.trailing_zeros() as usize;
+                // clear the lowest bit set to true
+                bits &= bits - 1;
+                if !f(block_number * block_size + block_position) {
+                    *block &= !Self::make_bitmask(block_position);
                 }
             }
         }
-    };
-}
+    }
 
-impl From<u8> for BitSet {
-    fn from(value: u8) -> Self {
-        // This implementation is simple. I don't care about the number of blocks here,
-        // as it's impossible to have something smaller than u8.
-        Self {
-            blocks: vec![usize::from(value)],
-            size: size_of::<u8>() * 8,
+    /// Flips the bit value at the position.
+    ///
+    /// Panics:
+    ///    - if the position is larger than the max bit number (which is size-1)
+    ///
+    pub fn flip(&mut self, position: usize) {
+        self.assert_position(position);
+
+        let bit_position = Self::get_bit_position(position);
+        let bitmask = Self::make_bitmask(bit_position.block_position);
+
+        self.blocks[bit_position.block_number] ^= bitmask;
+    }
+
+    /// Exchanges the values of the bits at both positions.
+    ///
+    /// Panics:
+    ///    - if any position is larger than the max bit number (which is size-1)
+    ///
+    pub fn swap(&mut self, i: usize, j: usize) {
+        let (left, right) = (self.get(i), self.get(j));
+        if left != right {
+            self.flip(i);
+            self.flip(j);
         }
     }
-}
 
-add_from_uint_trait! {u16}
-add_from_uint_trait! {u32}
-add_from_uint_trait! {u64}
-add_from_uint_trait! {u128}
-add_from_uint_trait! {usize}
+    /// Flips all the bits in the range [from; to).
+    ///
+    /// Panics:
+    ///    - if from is larger than to
+    ///    - if to is larger than the size
+    ///
+    pub fn flip_range(&mut self, from: usize, to: usize) {
+        self.assert_range(from, to);
+
+        for (block_number, bitmask) in Self::get_range_bitmasks(from, to) {
+            self.blocks[block_number] ^= bitmask;
+        }
+    }
+
+    /// Sets all the bits in the range [from; to) to the value.
+    ///
+    /// Panics:
+    ///    - if from is larger than to
+    ///    - if to is larger than the size
+    ///
+    pub fn set_all_range(&mut self, from: usize, to: usize, value: bool) {
+        self.assert_range(from, to);
+
+        for (block_number, bitmask) in Self::get_range_bitmasks(from, to) {
+            if value {
+                self.blocks[block_number] |= bitmask;
+            } else {
+                self.blocks[block_number] &= !bitmask;
+            }
+        }
+    }
+
+    /// Copies `len` bits of the source bitset, starting at the position `src_from`,
+    /// into this bitset, starting at the position `dst_from`.
+    ///
+    /// The bits are copied a block at a time, even if the positions are not aligned.
+    ///
+    /// Panics:
+    ///    - if the range [src_from; src_from + len) is outside the source size
+    ///    - if the range [dst_from; dst_from + len) is outside the size
+    ///
+    pub fn copy_bits_from(&mut self, src: &BitSet, src_from: usize, dst_from: usize, len: usize) {
+        src.assert_range(src_from, src_from + len);
+        self.assert_range(dst_from, dst_from + len);
+
+        let block_size = Self::block_size();
+        let mut copied = 0;
+        while copied < len {
+            let n = min(block_size, len - copied);
+            let bits = src.get_bits(src_from + copied, n);
+            self.set_bits(dst_from + copied, n, bits);
+            copied += n;
+        }
+    }
+}
+
+// utility functions
+impl BitSet {
+    /// Returns true if all bits are set. False if any is not set.
+    pub fn all(&self) -> bool {
+        let last_block_number = self.blocks.len() - 1;
+        for block in &self.blocks[..last_block_number] {
+            if *block != usize::MAX {
+                return false;
+            }
+        }
+        // the bits outside the size are never set
+        self.blocks[last_block_number] == self.last_block_bitmask()
+    }
+
+    /// Returns true if all none bit is set. False if all are not set.
+    pub fn any(&self) -> bool {
+        for block in &self.blocks {
+            if *block != 0 {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns the number of the lowest bits set to false,
+    /// before the first bit set to true.
+    ///
+    /// Returns the size if there is no bit set to true.
+    pub fn trailing_zeros(&self) -> usize {
+        for (block_number, block) in self.blocks.iter().enumerate() {
+            if *block != 0 {
+                return block_number * Self::block_size() + block.trailing_zeros() as usize;
+            }
+        }
+        self.size
+    }
+
+    /// Returns the number of the highest bits set to false,
+    /// after the last bit set to true. The bits outside the size are not counted.
+    ///
+    /// Returns the size if there is no bit set to true.
+    pub fn leading_zeros(&self) -> usize {
+        let unused_bits = self.blocks.len() * Self::block_size() - self.size;
+        for (blocks_above, block) in self.blocks.iter().rev().enumerate() {
+            if *block != 0 {
+                return blocks_above * Self::block_size() + block.leading_zeros() as usize
+                    - unused_bits;
+            }
+        }
+        self.size
+    }
+
+    /// Returns the position of the lowest bit set to true.
+    ///
+    /// Returns None if there is no bit set to true.
+    pub fn find_first_set(&self) -> Option<usize> {
+        match self.trailing_zeros() {
+            zeros if zeros == self.size => None,
+            zeros => Some(zeros),
+        }
+    }
+
+    /// Returns the position of the highest bit set to true.
+    ///
+    /// Returns None if there is no bit set to true.
+    pub fn find_last_set(&self) -> Option<usize> {
+        match self.leading_zeros() {
+            zeros if zeros == self.size => None,
+            zeros => Some(self.size - 1 - zeros),
+        }
+    }
+
+    /// Returns the lowest position of a bit set to true, which is not lower than `from`.
+    ///
+    /// Returns None if there is no such bit, also when `from` is not lower than the size.
+    pub fn next_set_bit(&self, from: usize) -> Option<usize> {
+        if from >= self.size {
+            return None;
+        }
+        let bit_position = Self::get_bit_position(from);
+        // the bits below the position in the first block are skipped
+        let bitmask = usize::MAX << bit_position.block_position;
+        let mut block = self.blocks[bit_position.block_number] & bitmask;
+        let mut block_number = bit_position.block_number;
+        loop {
+            if block != 0 {
+                return Some(block_number * Self::block_size() + block.trailing_zeros() as usize);
+            }
+            block_number += 1;
+            if block_number == self.blocks.len() {
+                return None;
+            }
+            block = self.blocks[block_number];
+        }
+    }
+
+    /// Returns the highest position of a bit set to true, which is not higher than `from`.
+    ///
+    /// Returns None if there is no such bit.
+    /// When `from` is not lower than the size, all the bits are checked.
+    pub fn prev_set_bit(&self, from: usize) -> Option<usize> {
+        let bit_position = Self::get_bit_position(min(from, self.size - 1));
+        // the bits above the position in the first block are skipped
+        let bitmask = Self::make_range_bitmask(0, bit_position.block_position + 1);
+        let mut block = self.blocks[bit_position.block_number] & bitmask;
+        let mut block_number = bit_position.block_number;
+        loop {
+            if block != 0 {
+                return Some(
+                    (block_number + 1) * Self::block_size() - 1 - block.leading_zeros() as usize,
+                );
+            }
+            if block_number == 0 {
+                return None;
+            }
+            block_number -= 1;
+            block = self.blocks[block_number];
+        }
+    }
+
+    /// Returns the lowest position of a bit set to true, the same as `find_first_set()`.
+    pub fn min(&self) -> Option<usize> {
+        self.find_first_set()
+    }
+
+    /// Returns the highest position of a bit set to true, the same as `find_last_set()`.
+    pub fn max(&self) -> Option<usize> {
+        self.find_last_set()
+    }
+
+    /// Returns the position of the lowest bit set to false.
+    ///
+    /// Returns None if all the bits are set to true.
+    pub fn first_zero(&self) -> Option<usize> {
+        let last_block_number = self.blocks.len() - 1;
+        for (block_number, block) in self.blocks.iter().enumerate() {
+            let mut zeros = !block;
+            if block_number == last_block_number {
+                zeros &= self.last_block_bitmask();
+            }
+            if zeros != 0 {
+                return Some(block_number * Self::block_size() + zeros.trailing_zeros() as usize);
+            }
+        }
+        None
+    }
+
+    /// Returns the position of the highest bit set to false.
+    ///
+    /// Returns None if all the bits are set to true.
+    pub fn last_zero(&self) -> Option<usize> {
+        let last_block_number = self.blocks.len() - 1;
+        for (block_number, block) in self.blocks.iter().enumerate().rev() {
+            let mut zeros = !block;
+            if block_number == last_block_number {
+                zeros &= self.last_block_bitmask();
+            }
+            if zeros != 0 {
+                return Some(
+                    (block_number + 1) * Self::block_size() - 1 - zeros.leading_zeros() as usize,
+                );
+            }
+        }
+        None
+    }
+
+    /// Returns the number of the lowest bits set to true,
+    /// before the first bit set to false.
+    ///
+    /// Returns the size if all the bits are set to true.
+    pub fn count_trailing_ones(&self) -> usize {
+        self.first_zero().unwrap_or(self.size)
+    }
+
+    /// Returns the number of the highest bits set to true,
+    /// after the last bit set to false. The bits outside the size are not counted.
+    ///
+    /// Returns the size if all the bits are set to true.
+    pub fn count_leading_ones(&self) -> usize {
+        match self.last_zero() {
+            Some(position) => self.size - 1 - position,
+            None => self.size,
+        }
+    }
+
+    /// Returns a well distributed 64 bit hash of the bitset,
+    /// made by folding the blocks with the splitmix64 mixing function.
+    ///
+    /// This is meant for things like bloom filters, and it's not a cryptographic hash.
+    /// It's not related to the `Hash` implementation used for the hash maps.
+    pub fn mix_hash(&self) -> u64 {
+        let mut hash = Self::mix(self.size as u64);
+        for block in &self.blocks {
+            hash = Self::mix(hash ^ *block as u64);
+        }
+        hash
+    }
+
+    /// Returns the fraction of bits set to true, in the range [0.0; 1.0].
+    pub fn density(&self) -> f64 {
+        // the size is never zero, so there is no division by zero
+        self.count() as f64 / self.size as f64
+    }
+
+    /// Flips all the bits.
+    pub fn negate(&mut self) {
+        for block in self.blocks.iter_mut() {
+            *block = !*block;
+        }
+        self.clear_unused_bits();
+    }
+
+    /// Flips all the bits, the same as `negate()`.
+    pub fn toggle_all(&mut self) {
+        self.negate();
+    }
+
+    /// Flips all the bits in the range [from; to), the same as `flip_range()`.
+    ///
+    /// Panics:
+    ///    - if from is larger than to
+    ///    - if to is larger than the size
+    ///
+    pub fn toggle_range(&mut self, from: usize, to: usize) {
+        self.flip_range(from, to);
+    }
+
+    /// Returns a new bitset with all the bits flipped.
+    pub fn complement(&self) -> BitSet {
+        let mut output = self.clone();
+        output.negate();
+        output
+    }
+
+    /// Flips all the bits, which are set in the mask. The other bits are not changed.
+    ///
+    /// Panics:
+    ///    - if the mask has a different size
+    ///
+    pub fn toggle_masked(&mut self, mask: &Self) {
+        self.combine(mask, |block, mask_block| block ^ mask_block);
+    }
+
+    /// Returns the number of bits allowed to use.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the number of bits, which can be used without reallocating the memory.
+    pub fn capacity(&self) -> usize {
+        self.blocks.capacity() * Self::block_size()
+    }
+
+    /// Returns the approximate number of bytes used by the bitset,
+    /// including the memory reserved for the blocks.
+    pub fn memory_usage(&self) -> usize {
+        self.blocks.capacity() * size_of::<usize>() + size_of::<Self>()
+    }
+
+    /// Returns true if none bit is set.
+    ///
+    /// This is not related to the `len()`, which is never zero.
+    pub fn is_empty(&self) -> bool {
+        !self.any()
+    }
+
+    /// Sets all the bits to the value.
+    pub fn set_all(&mut self, value: bool) {
+        self.blocks.fill(if value { usize::MAX } else { 0 });
+        self.clear_unused_bits();
+    }
+
+    /// Sets all the bits to false. The size is not changed.
+    pub fn clear(&mut self) {
+        self.blocks.fill(0);
+    }
+
+    /// Returns number of bits set to true.
+    ///
+    /// The blocks are processed in chunks of 8, with a separate sum for each chunk,
+    /// so the compiler can keep it in a register and vectorize the loop.
+    pub fn count(&self) -> usize {
+        let mut chunks = self.blocks.chunks_exact(8);
+        let mut res = 0;
+        for chunk in &mut chunks {
+            let mut chunk_res = 0;
+            for block in chunk {
+                chunk_res += block.count_ones();
+            }
+            res += chunk_res as usize;
+        }
+        for block in chunks.remainder() {
+            res += block.count_ones() as usize;
+        }
+        res
+    }
+
+    /// Returns true if the number of bits set to true is odd.
+    ///
+    /// The blocks are xored together first, so only one block is counted.
+    pub fn parity(&self) -> bool {
+        let folded = self.blocks.iter().fold(0, |res, block| res ^ block);
+        folded.count_ones() & 1 == 1
+    }
+
+    /// Returns true if exactly one bit is set to true.
+    pub fn is_single_bit(&self) -> bool {
+        self.single_bit_index().is_some()
+    }
+
+    /// Returns the position of the only bit set to true.
+    ///
+    /// Returns None if there are no bits, or more than one bit, set to true.
+    pub fn single_bit_index(&self) -> Option<usize> {
+        let mut res = None;
+        for (block_number, block) in self.blocks.iter().enumerate() {
+            if *block == 0 {
+                continue;
+            }
+            // more than one bit in the block, or another block with a bit
+            if block & (block - 1) != 0 || res.is_some() {
+                return None;
+            }
+            res = Some(block_number * Self::block_size() + block.trailing_zeros() as usize);
+        }
+        res
+    }
+
+    /// Returns the number of the neighbouring bits with different values.
+    pub fn count_transitions(&self) -> usize {
+        let block_size = Self::block_size();
+        let last_block_number = self.blocks.len() - 1;
+        let mut res = 0;
+
+        for (block_number, block) in self.blocks.iter().enumerate() {
+            // each bit is compared with the next one
+            let mut next_bits = block >> 1;
+            if block_number < last_block_number {
+                next_bits |= (self.blocks[block_number + 1] & 1) << (block_size - 1);
+            }
+            let mut transitions = block ^ next_bits;
+            if block_number == last_block_number {
+                // the highest bit has no next one
+                let pairs = self.size - block_number * block_size - 1;
+                transitions = if pairs == 0 {
+                    0
+                } else {
+                    transitions & Self::make_range_bitmask(0, pairs)
+                };
+            }
+            res += transitions.count_ones() as usize;
+        }
+        res
+    }
+
+    /// Returns number of bits set to false.
+    ///
+    /// The bits of the last block, which are outside the size, are not counted.
+    pub fn count_zeros(&self) -> usize {
+        let last_block_number = self.blocks.len() - 1;
+        let mut res = 0;
+        for (block_number, block) in self.blocks.iter().enumerate() {
+            if block_number == last_block_number {
+                res += (block | !self.last_block_bitmask()).count_zeros() as usize;
+            } else {
+                res += block.count_zeros() as usize;
+            }
+        }
+        res
+    }
+
+    /// Returns number of bits set to true in the range [from; to).
+    ///
+    /// Panics:
+    ///    - if the range is outside the size
+    ///
+    pub fn count_in_range(&self, from: usize, to: usize) -> usize {
+        self.assert_range(from, to);
+
+        let mut res = 0;
+        for (block_number, bitmask) in Self::get_range_bitmasks(from, to) {
+            res += (self.blocks[block_number] & bitmask).count_ones() as usize;
+        }
+        res
+    }
+
+    /// Copies the bitset into the `dst`.
+    ///
+    /// Unlike `clone()`, which always allocates new blocks,
+    /// this reuses the `dst` blocks allocation if it's large enough.
+    pub fn clone_into(&self, dst: &mut BitSet) {
+        dst.blocks.clone_from(&self.blocks);
+        dst.size = self.size;
+    }
+
+    /// Returns the number of the maximal runs of bits with the value.
+    ///
+    /// A run starts at each bit with the value, which is the lowest bit
+    /// or whose lower neighbour has a different value.
+    pub fn count_runs(&self, value: bool) -> usize {
+        let last_block_number = self.blocks.len() - 1;
+        let mut runs = 0;
+        // the highest bit of the previous block, moved to the lowest position
+        let mut carry = 0;
+
+        for (block_number, block) in self.blocks.iter().enumerate() {
+            let mut block = if value { *block } else { !*block };
+            if block_number == last_block_number {
+                block &= self.last_block_bitmask();
+            }
+            let previous_bits = (block << 1) | carry;
+            runs += (block & !previous_bits).count_ones() as usize;
+            carry = block >> (Self::block_size() - 1);
+        }
+        runs
+    }
+
+    /// Returns the length of the longest run of the neighbouring bits set to true.
+    pub fn longest_run_ones(&self) -> usize {
+        self.longest_run(true)
+    }
+
+    /// Returns the length of the longest run of the neighbouring bits set to false.
+    pub fn longest_run_zeros(&self) -> usize {
+        self.longest_run(false)
+    }
+
+    /// Finds the longest run by jumping over the whole runs.
+    fn longest_run(&self, value: bool) -> usize {
+        let mut res = 0;
+        let mut position = 0;
+        while position < self.size {
+            // the runs of both values alternate, so the other ones are skipped
+            let length = self.run_length(position, value);
+            res = max(res, length);
+            position += length;
+            position += self.run_length(position, !value);
+        }
+        res
+    }
+
+    /// Enlarges the bitset to the required size.
+    ///
+    /// All the new bits are set to false.
+    ///
+    /// The error is returned when the new size is not larger than the current one.
+    ///
+    pub fn enlarge(&mut self, new_size: usize) -> Result<(), BitSetError> {
+        if new_size <= self.size {
+            return Err(BitSetError::EnlargeError {
+                from: self.size,
+                to: new_size,
+            });
+        }
+        self.resize(new_size);
+        Ok(())
+    }
+}
+
+// Resizing functions
+impl BitSet {
+    /// Resizes the bitset to the new size.
+    ///
+    /// When the bitset grows, each new bit is set to the value returned by `f`,
+    /// which is called with the absolute position of the bit,
+    /// so for a bitset of size 10 resized to 12, it's called with 10 and 11.
+    ///
+    /// When the bitset shrinks, the bits outside the new size are dropped,
+    /// and `f` is not called at all.
+    ///
+    /// Panics:
+    ///    - when to_size=0
+    ///
+    pub fn resize_with<F: FnMut(usize) -> bool>(&mut self, to_size: usize, mut f: F) {
+        if to_size == 0 {
+            panic!("Resizing BitSet to zero bits is not allowed.");
+        }
+        let old_size = self.size;
+
+        self.blocks.resize(Self::blocks_number(to_size), 0);
+        self.size = to_size;
+        self.clear_unused_bits();
+
+        for position in old_size..to_size {
+            if f(position) {
+                self.set(position, true);
+            }
+        }
+    }
+
+    /// Resizes the bitset to the new size.
+    ///
+    /// When the bitset grows, the new bits are set to false.
+    /// When the bitset shrinks, the bits outside the new size are dropped.
+    ///
+    /// Panics:
+    ///    - when to_size=0
+    ///
+    pub fn resize(&mut self, to_size: usize) {
+        self.resize_with(to_size, |_| false);
+    }
+
+    /// Enlarges the bitset by the number of bits, the new bits are set to false.
+    ///
+    /// Panics:
+    ///    - when the new size would overflow usize
+    ///
+    pub fn grow(&mut self, by: usize) {
+        let to_size = self
+            .size
+            .checked_add(by)
+            .expect("Growing BitSet above usize::MAX bits is not allowed.");
+        self.resize(to_size);
+    }
+
+    /// Shrinks the bitset by the number of bits, dropping the highest bits.
+    ///
+    /// Panics:
+    ///    - when the bitset would have zero bits
+    ///
+    pub fn shrink(&mut self, by: usize) {
+        if by >= self.size {
+            panic!("Shrinking BitSet to zero bits is not allowed.");
+        }
+        self.resize(self.size - by);
+    }
+
+    /// Shrinks the bitset to the new size, keeping only the lowest bits.
+    ///
+    /// Nothing is changed if the bitset is not larger than the new size.
+    ///
+    /// Panics:
+    ///    - when to_size=0
+    ///
+    pub fn truncate(&mut self, to_size: usize) {
+        if to_size == 0 {
+            panic!("Truncating BitSet to zero bits is not allowed.");
+        }
+        if to_size < self.size {
+            self.resize(to_size);
+        }
+    }
+
+    /// Enlarges the bitset by the size of the other one,
+    /// and copies the other bits into the new positions,
+    /// so the bit `j` of the other bitset is at the position `old_size + j`.
+    pub fn append(&mut self, other: &Self) {
+        let block_size = Self::block_size();
+        let old_size = self.size;
+        self.resize(old_size + other.size);
+
+        let blocks_shift = old_size / block_size;
+        let bits_shift = old_size % block_size;
+        for (block_number, block) in other.blocks.iter().enumerate() {
+            let target = blocks_shift + block_number;
+            self.blocks[target] |= block << bits_shift;
+            if bits_shift != 0 && target + 1 < self.blocks.len() {
+                self.blocks[target + 1] |= block >> (block_size - bits_shift);
+            }
+        }
+    }
+
+    /// Returns a new bitset with the bits of the other one placed above the bits of this one,
+    /// the same as `append()` on a copy.
+    pub fn concat(&self, other: &Self) -> BitSet {
+        let mut output = BitSet::with_capacity(self.size, self.size + other.size);
+        output.blocks.copy_from_slice(&self.blocks);
+        output.append(other);
+        output
+    }
+
+    /// Returns two new bitsets, the first one with the bits [0; position),
+    /// the second one with the bits [position; size) moved to the lowest positions.
+    ///
+    /// Panics:
+    ///    - if the position is 0 or not lower than the size,
+    ///      as one of the bitsets would have zero bits
+    ///
+    pub fn split_at(&self, position: usize) -> (BitSet, BitSet) {
+        if position == 0 || position >= self.size {
+            panic!(
+                "Split position [{}] is outside available range: [1, {}]",
+                position,
+                self.size - 1
+            );
+        }
+        let mut low = self.clone();
+        low.truncate(position);
+
+        let mut high = BitSet::new(self.size - position);
+        high.copy_bits_from(self, position, 0, self.size - position);
+        (low, high)
+    }
+
+    /// Releases the memory reserved for the blocks, which are not needed for the size.
+    ///
+    /// The size and the bits are not changed.
+    pub fn shrink_to_fit(&mut self) {
+        self.blocks.shrink_to_fit();
+    }
+
+    /// Reserves the memory for at least `additional_bits` more bits,
+    /// so the bitset can grow to that size without reallocating.
+    ///
+    /// The size and the bits are not changed.
+    ///
+    /// Panics:
+    ///    - when the reserved size would overflow usize
+    ///
+    pub fn reserve(&mut self, additional_bits: usize) {
+        let to_size = self
+            .size
+            .checked_add(additional_bits)
+            .expect("Reserving BitSet above usize::MAX bits is not allowed.");
+        let blocks = Self::blocks_number(to_size);
+        self.blocks.reserve(blocks - self.blocks.len());
+    }
+
+    /// Adds a new highest bit with the value, enlarging the bitset by one bit.
+    pub fn push(&mut self, value: bool) {
+        let position = self.size;
+        self.resize(position + 1);
+        self.set(position, value);
+    }
+
+    /// Removes the highest bit and returns its value, shrinking the bitset by one bit.
+    ///
+    /// A BitSet with zero bits is not allowed, so for a bitset with just one bit
+    /// this returns None and the bitset is not changed.
+    pub fn pop(&mut self) -> Option<bool> {
+        if self.size == 1 {
+            return None;
+        }
+        let value = self.get(self.size - 1);
+        self.resize(self.size - 1);
+        Some(value)
+    }
+
+    /// Sets the bit value at the position, enlarging the bitset to `position + 1` bits
+    /// when the position is outside the size.
+    ///
+    /// The new bits between the old size and the position are set to false.
+    /// The bitset never shrinks.
+    pub fn set_grow(&mut self, position: usize, value: bool) {
+        if position >= self.size {
+            self.resize(position + 1);
+        }
+        self.set(position, value);
+    }
+
+    /// Enlarges the bitset, so it uses at least the given number of blocks.
+    ///
+    /// The size is set to all the bits of the blocks, and the new bits are set to false.
+    /// Nothing is changed if the bitset already has enough bits.
+    pub fn grow_to_block_count(&mut self, blocks: usize) {
+        let to_size = blocks * Self::block_size();
+        if to_size > self.size {
+            self.resize_with(to_size, |_| false);
+        }
+    }
+}
+
+// Bit order functions
+impl BitSet {
+    /// Rotates the bits towards the higher positions by `n`.
+    ///
+    /// The bits shifted above the size appear at the lowest positions.
+    /// Rotating by the size doesn't change anything.
+    pub fn rotate_left(&mut self, n: usize) {
+        let n = n % self.size;
+        if n == 0 {
+            return;
+        }
+        let carry = &*self >> (self.size - n);
+        *self <<= n;
+        for (block, carry_block) in self.blocks.iter_mut().zip(carry.blocks.iter()) {
+            *block |= carry_block;
+        }
+    }
+
+    /// Rotates the bits towards the lower positions by `n`.
+    ///
+    /// The bits shifted below zero appear at the highest positions.
+    /// Rotating by the size doesn't change anything.
+    pub fn rotate_right(&mut self, n: usize) {
+        let n = n % self.size;
+        self.rotate_left(self.size - n);
+    }
+
+    /// Shifts the bits towards the higher positions by `n`, like `<<=`.
+    ///
+    /// Returns a bitset of the same size with the bits shifted above the size,
+    /// kept at their original positions, so `(shifted >> n) | carry` gives the original bitset.
+    pub fn shift_left_carry(&mut self, n: usize) -> BitSet {
+        let mut carry = self.clone();
+        if n < self.size {
+            carry.set_all_range(0, self.size - n, false);
+        }
+        *self <<= n;
+        carry
+    }
+
+    /// Shifts the bits towards the lower positions by `n`, like `>>=`.
+    ///
+    /// Returns a bitset of the same size with the bits shifted below zero,
+    /// kept at their original positions, so `(shifted << n) | carry` gives the original bitset.
+    pub fn shift_right_carry(&mut self, n: usize) -> BitSet {
+        let mut carry = self.clone();
+        carry.set_all_range(min(n, self.size), self.size, false);
+        *self >>= n;
+        carry
+    }
+
+    /// Rotates the bits of each block towards the higher positions by `n`,
+    /// independently, so the bits are never moved to another block.
+    ///
+    /// This doesn't give a rotated bitset, use `rotate_left()` for that.
+    ///
+    /// The last block is rotated only within the bits inside the size,
+    /// so no bit is moved outside the bitset.
+    pub fn rotate_each_block(&mut self, n: usize) {
+        let block_size = Self::block_size();
+        let last_block_number = self.blocks.len() - 1;
+        let last_block_bits = self.size - last_block_number * block_size;
+
+        for block in self.blocks[..last_block_number].iter_mut() {
+            *block = block.rotate_left((n % block_size) as u32);
+        }
+
+        let n = n % last_block_bits;
+        if n != 0 {
+            let block = &mut self.blocks[last_block_number];
+            *block = ((*block << n) | (*block >> (last_block_bits - n)))
+                & Self::make_range_bitmask(0, last_block_bits);
+        }
+    }
+
+    /// Reverses the order of the bits, so the bit `i` is swapped with the bit `size-1-i`.
+    pub fn reverse(&mut self) {
+        self.blocks.reverse();
+        for block in self.blocks.iter_mut() {
+            *block = block.reverse_bits();
+        }
+        // the unused bits of the last block are now at the bottom
+        let unused_bits = self.blocks.len() * Self::block_size() - self.size;
+        self.shift_blocks_right(unused_bits);
+    }
+
+    /// Returns a new bitset, where the bit `i` has the value of the bit `permutation[i]`.
+    ///
+    /// Panics:
+    ///    - if the permutation length is different than the size
+    ///    - if the permutation contains a position outside the size, or a repeated one
+    ///
+    pub fn permute(&self, permutation: &[usize]) -> BitSet {
+        if permutation.len() != self.size {
+            panic!(
+                "Permutation length {} is different than the size {}.",
+                permutation.len(),
+                self.size
+            );
+        }
+        let mut used = BitSet::new(self.size);
+        let mut output = BitSet::new(self.size);
+        for (i, position) in permutation.iter().enumerate() {
+            if used.try_set(*position, true) != Some(false) {
+                panic!(
+                    "Permutation position [{}] is repeated or outside available range: [0, {}]",
+                    position,
+                    self.size - 1
+                );
+            }
+            output.set(i, self.get(*position));
+        }
+        output
+    }
+
+    /// Returns true if the bits are the same when read in the reversed order,
+    /// so the bit `i` is equal to the bit `size-1-i`.
+    pub fn is_palindrome(&self) -> bool {
+        let mut reversed = self.clone();
+        reversed.reverse();
+        reversed == *self
+    }
+}
+
+// Set operations
+impl BitSet {
+    /// Sets the bits, which are set in this or the other bitset.
+    /// For bitsets with different sizes use `union_extend()`.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn union(&mut self, other: &Self) {
+        self.combine(other, |left, right| left | right);
+    }
+
+    /// Leaves only the bits, which are set in both bitsets.
+    /// For bitsets with different sizes use `intersect_extend()`.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn intersect(&mut self, other: &Self) {
+        self.combine(other, |left, right| left & right);
+    }
+
+    /// Leaves only the bits, which are not set in the other bitset.
+    /// For bitsets with different sizes use `difference_extend()`.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn difference(&mut self, other: &Self) {
+        self.combine(other, |left, right| left & !right);
+    }
+
+    /// Leaves only the bits, which are set in exactly one of the bitsets.
+    /// For bitsets with different sizes use `symmetric_difference_extend()`.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn symmetric_difference(&mut self, other: &Self) {
+        self.combine(other, |left, right| left ^ right);
+    }
+
+    /// Returns a new bitset with the bits set in any of the bitsets.
+    ///
+    /// Returns None if there are no bitsets.
+    ///
+    /// Panics:
+    ///    - if the bitsets have different sizes
+    ///
+    pub fn union_all(sets: &[BitSet]) -> Option<BitSet> {
+        let (first, rest) = sets.split_first()?;
+        let mut output = first.clone();
+        for set in rest {
+            output.union(set);
+        }
+        Some(output)
+    }
+
+    /// Returns a new bitset with the bits set in all the bitsets.
+    ///
+    /// Returns None if there are no bitsets.
+    ///
+    /// Panics:
+    ///    - if the bitsets have different sizes
+    ///
+    pub fn intersect_all(sets: &[BitSet]) -> Option<BitSet> {
+        let (first, rest) = sets.split_first()?;
+        let mut output = first.clone();
+        for set in rest {
+            output.intersect(set);
+        }
+        Some(output)
+    }
+
+    /// The same as `union()`, but returns the error instead of panicking,
+    /// when the other bitset has a different size. The bitset is not changed then.
+    pub fn try_union(&mut self, other: &Self) -> Result<(), SizeMismatch> {
+        self.check_same_size(other)?;
+        self.union(other);
+        Ok(())
+    }
+
+    /// The same as `intersect()`, but returns the error instead of panicking,
+    /// when the other bitset has a different size. The bitset is not changed then.
+    pub fn try_intersect(&mut self, other: &Self) -> Result<(), SizeMismatch> {
+        self.check_same_size(other)?;
+        self.intersect(other);
+        Ok(())
+    }
+
+    /// The same as `difference()`, but returns the error instead of panicking,
+    /// when the other bitset has a different size. The bitset is not changed then.
+    pub fn try_difference(&mut self, other: &Self) -> Result<(), SizeMismatch> {
+        self.check_same_size(other)?;
+        self.difference(other);
+        Ok(())
+    }
+
+    /// The same as `symmetric_difference()`, but returns the error instead of panicking,
+    /// when the other bitset has a different size. The bitset is not changed then.
+    pub fn try_symmetric_difference(&mut self, other: &Self) -> Result<(), SizeMismatch> {
+        self.check_same_size(other)?;
+        self.symmetric_difference(other);
+        Ok(())
+    }
+
+    /// The same as `union()`, but the bitsets can have different sizes.
+    ///
+    /// The missing higher bits of the smaller bitset are treated as false,
+    /// and this bitset is enlarged to the larger size if needed.
+    pub fn union_extend(&mut self, other: &Self) {
+        self.combine_extend(other, |left, right| left | right);
+    }
+
+    /// The same as `intersect()`, but the bitsets can have different sizes.
+    ///
+    /// The missing higher bits of the smaller bitset are treated as false,
+    /// and this bitset is enlarged to the larger size if needed.
+    pub fn intersect_extend(&mut self, other: &Self) {
+        self.combine_extend(other, |left, right| left & right);
+    }
+
+    /// The same as `difference()`, but the bitsets can have different sizes.
+    ///
+    /// The missing higher bits of the smaller bitset are treated as false,
+    /// and this bitset is enlarged to the larger size if needed.
+    pub fn difference_extend(&mut self, other: &Self) {
+        self.combine_extend(other, |left, right| left & !right);
+    }
+
+    /// The same as `symmetric_difference()`, but the bitsets can have different sizes.
+    ///
+    /// The missing higher bits of the smaller bitset are treated as false,
+    /// and this bitset is enlarged to the larger size if needed.
+    pub fn symmetric_difference_extend(&mut self, other: &Self) {
+        self.combine_extend(other, |left, right| left ^ right);
+    }
+
+    /// Sets to false all the bits, which are set in the other bitset.
+    ///
+    /// This is the same as `self &= !other`, without creating the negated bitset.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn and_not(&mut self, other: &Self) {
+        self.combine(other, |left, right| left & !right);
+    }
+
+    /// Returns a new bitset with the bits set in this bitset and not set in the other one.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn and_not_owned(&self, other: &Self) -> BitSet {
+        let mut output = self.clone();
+        output.and_not(other);
+        output
+    }
+
+    /// Returns the number of bits, which would be left set after `and_not()`,
+    /// without changing this bitset.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn and_not_count(&self, other: &Self) -> usize {
+        self.count_combined(other, |left, right| left & !right)
+    }
+}
+
+// Comparison functions
+impl BitSet {
+    /// Returns true if the bits of this bitset are equal to the lowest bits of the other one.
+    ///
+    /// It's always false when this bitset is larger than the other one.
+    pub fn is_bit_prefix_of(&self, other: &Self) -> bool {
+        if self.size > other.size {
+            return false;
+        }
+        let last_block_number = self.blocks.len() - 1;
+        let last_block_bitmask = self.last_block_bitmask();
+
+        for (block_number, (left, right)) in self.blocks.iter().zip(other.blocks.iter()).enumerate()
+        {
+            let difference = if block_number == last_block_number {
+                (left ^ right) & last_block_bitmask
+            } else {
+                left ^ right
+            };
+            if difference != 0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns true if the bits in the range [0, len) are the same in both bitsets.
+    ///
+    /// The sizes of the bitsets don't need to match.
+    ///
+    /// Panics:
+    ///    - if len is larger than the size of any of the bitsets
+    ///
+    pub fn eq_bits(&self, other: &Self, len: usize) -> bool {
+        self.assert_range(0, len);
+        other.assert_range(0, len);
+
+        Self::get_range_bitmasks(0, len).all(|(block_number, bitmask)| {
+            self.blocks[block_number] & bitmask == other.blocks[block_number] & bitmask
+        })
+    }
+
+    /// Returns true if all the bits set in this bitset are also set in the other one.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.assert_same_size(other);
+
+        self.blocks
+            .iter()
+            .zip(other.blocks.iter())
+            .all(|(left, right)| left & !right == 0)
+    }
+
+    /// Returns true if all the bits set in the other bitset are also set in this one.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns true if this bitset is a subset of the other one, and they are not equal.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn is_proper_subset(&self, other: &Self) -> bool {
+        self.is_subset(other) && self.blocks != other.blocks
+    }
+
+    /// Returns true if this bitset is a superset of the other one, and they are not equal.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn is_proper_superset(&self, other: &Self) -> bool {
+        other.is_proper_subset(self)
+    }
+
+    /// Returns the number of positions where the bitsets differ.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn hamming_distance(&self, other: &Self) -> usize {
+        self.count_combined(other, |left, right| left ^ right)
+    }
+
+    /// Returns the number of bits set to true in both bitsets.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn intersection_count(&self, other: &Self) -> usize {
+        self.count_combined(other, |left, right| left & right)
+    }
+
+    /// Returns the number of bits set to true in any of the bitsets.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn union_count(&self, other: &Self) -> usize {
+        self.count_combined(other, |left, right| left | right)
+    }
+
+    /// Returns the number of bits set to true in exactly one of the bitsets,
+    /// the same as `hamming_distance()`.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn symmetric_difference_count(&self, other: &Self) -> usize {
+        self.count_combined(other, |left, right| left ^ right)
+    }
+
+    /// Returns the Jaccard similarity: the intersection count divided by the union count.
+    ///
+    /// Two bitsets without any bits set to true are considered identical, with the similarity 1.0.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn jaccard_similarity(&self, other: &Self) -> f64 {
+        let union = self.union_count(other);
+        if union == 0 {
+            return 1.0;
+        }
+        self.intersection_count(other) as f64 / union as f64
+    }
+
+    /// Returns the lowest position where the bitsets differ, or None if they are equal.
+    ///
+    /// Panics:
+    ///    - if the other bitset has a different size
+    ///
+    pub fn first_difference(&self, other: &Self) -> Option<usize> {
+        self.assert_same_size(other);
+
+        for (block_number, (left, right)) in self.blocks.iter().zip(other.blocks.iter()).enumerate()
+        {
+            let difference = left ^ right;
+            if difference != 0 {
+                return Some(
+                    block_number * Self::block_size() + difference.trailing_zeros() as usize,
+                );
+            }
+        }
+        None
+    }
+}
+
+// Rank and select functions
+impl BitSet {
+    /// Returns the number of bits set to true in the range [0; position).
+    ///
+    /// Panics:
+    ///    - if the position is larger than the size
+    ///
+    pub fn rank(&self, position: usize) -> usize {
+        self.assert_range(0, position);
+
+        let bit_position = Self::get_bit_position(position);
+        let mut res = 0;
+        for block in &self.blocks[..bit_position.block_number] {
+            res += block.count_ones() as usize;
+        }
+        if bit_position.block_position != 0 {
+            let bitmask = Self::make_range_bitmask(0, bit_position.block_position);
+            res += (self.blocks[bit_position.block_number] & bitmask).count_ones() as usize;
+        }
+        res
+    }
+
+    /// Returns the position of the `n`-th bit set to true, counting from zero.
+    ///
+    /// Returns None if there are not enough bits set to true.
+    pub fn select(&self, n: usize) -> Option<usize> {
+        let mut remaining = n;
+        for (block_number, block) in self.blocks.iter().enumerate() {
+            let ones = block.count_ones() as usize;
+            if remaining >= ones {
+                remaining -= ones;
+                continue;
+            }
+            let mut block = *block;
+            for _ in 0..remaining {
+                // clear the lowest bit set to true
+                block &= block - 1;
+            }
+            return Some(block_number * Self::block_size() + block.trailing_zeros() as usize);
+        }
+        None
+    }
+}
+
+// Iterators
+impl BitSet {
+    /// Returns the positions of the bits set to true, in ascending order.
+    ///
+    /// The iterator can be reversed to get the positions from the highest.
+    pub fn iter_ones(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        BitPositions::new(self, true)
+    }
+
+    /// Returns the positions of the bits set to false, in ascending order.
+    ///
+    /// The bits of the last block, which are outside the size, are not returned.
+    pub fn iter_zeros(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        BitPositions::new(self, false)
+    }
+
+    /// Calls the function for the positions of the bits set to true, in ascending order.
+    pub fn for_each_set_bit<F: FnMut(usize)>(&self, mut f: F) {
+        let block_size = Self::block_size();
+        for (block_number, block) in self.blocks.iter().enumerate() {
+            let mut bits = *block;
+            while bits != 0 {
+                f(block_number * block_size + bits.trailing_zeros() as usize);
+                // clear the lowest bit set to true
+                bits &= bits - 1;
+            }
+        }
+    }
+
+    /// Returns the maximal runs of the neighbouring bits with the same value,
+    /// as `(start, length, value)`, from the lowest bits.
+    ///
+    /// The runs cover all the bits, and the values of the next runs alternate.
+    pub fn runs(&self) -> impl Iterator<Item = (usize, usize, bool)> + '_ {
+        let mut position = 0;
+        std::iter::from_fn(move || {
+            if position >= self.size {
+                return None;
+            }
+            let start = position;
+            let value = self.get(start);
+            let length = self.run_length(start, value);
+            position += length;
+            Some((start, length, value))
+        })
+    }
+
+    /// Returns the positions of the bits set to true, in ascending order.
+    ///
+    /// The vector is allocated once, with the capacity equal to the number of the positions.
+    pub fn collect_ones(&self) -> Vec<usize> {
+        let mut ones = Vec::with_capacity(self.count());
+        ones.extend(self.iter_ones());
+        ones
+    }
+}
+
+/// Iterator over the positions of the bits with the given value.
+///
+/// The not returned positions are kept as the range [front; back).
+struct BitPositions<'a> {
+    bitset: &'a BitSet,
+    // xored with the blocks, so the searched bits are always set to true
+    flip: usize,
+    front: usize,
+    back: usize,
+}
+
+impl<'a> BitPositions<'a> {
+    fn new(bitset: &'a BitSet, value: bool) -> Self {
+        BitPositions {
+            bitset,
+            flip: if value { 0 } else { usize::MAX },
+            front: 0,
+            back: bitset.size,
+        }
+    }
+
+    /// Returns the block with the searched bits set to true.
+    fn block(&self, block_number: usize) -> usize {
+        self.bitset.blocks[block_number] ^ self.flip
+    }
+}
+
+impl Iterator for BitPositions<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block_size = BitSet::block_size();
+        while self.front < self.back {
+            let block_number = self.front / block_size;
+            let block_start = block_number * block_size;
+            let end = min(self.back - block_start, block_size);
+            let bitmask = BitSet::make_range_bitmask(self.front - block_start, end);
+
+            let bits = self.block(block_number) & bitmask;
+            if bits != 0 {
+                let position = block_start + bits.trailing_zeros() as usize;
+                self.front = position + 1;
+                return Some(position);
+            }
+            self.front = block_start + block_size;
+        }
+        None
+    }
+}
+
+impl DoubleEndedIterator for BitPositions<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let block_size = BitSet::block_size();
+        while self.front < self.back {
+            let block_number = (self.back - 1) / block_size;
+            let block_start = block_number * block_size;
+            let start = max(self.front, block_start) - block_start;
+            let bitmask = BitSet::make_range_bitmask(start, self.back - block_start);
+
+            let bits = self.block(block_number) & bitmask;
+            if bits != 0 {
+                let position = block_start + block_size - 1 - bits.leading_zeros() as usize;
+                self.back = position;
+                return Some(position);
+            }
+            self.back = block_start;
+        }
+        None
+    }
+}
+
+// Raw blocks functions
+impl BitSet {
+    /// Returns the blocks split into two slices at the given block number.
+    ///
+    /// The first slice contains the blocks [0; block_number),
+    /// the second one contains the rest of them.
+    ///
+    /// Panics:
+    ///    - if the block number is larger than the number of blocks
+    ///
+    pub fn split_blocks_at(&self, block_number: usize) -> (&[usize], &[usize]) {
+        if block_number > self.blocks.len() {
+            panic!(
+                "Block number [{}] is outside available range: [0, {}]",
+                block_number,
+                self.blocks.len()
+            );
+        }
+        self.blocks.split_at(block_number)
+    }
+
+    /// Returns all the blocks storing the bits, the bit `i` is stored in the block `i / usize::BITS`.
+    ///
+    /// The last block may have more bits than the size. They are kept false,
+    /// but they are not part of the bitset, so mask them if that matters.
+    pub fn raw_blocks(&self) -> &[usize] {
+        &self.blocks
+    }
+
+    /// Returns the bits packed into the 64 bit words, the bit `i` is stored
+    /// in the word `i / 64`, the same way on all the targets.
+    ///
+    /// The bits of the last word, which are outside the size, are set to false.
+    pub fn to_u64_vec(&self) -> Vec<u64> {
+        let mut res = vec![0; self.size.div_ceil(64)];
+        for (block_number, block) in self.blocks.iter().enumerate() {
+            let position = block_number * Self::block_size();
+            res[position / 64] |= (*block as u64) << (position % 64);
+        }
+        res
+    }
+
+    /// Returns the number of bits stored in the raw blocks, the same as `len()`.
+    pub fn bits_size(&self) -> usize {
+        self.size
+    }
+}
+
+macro_rules! add_from_uint_trait {
+    ($t:ty) => {
+        impl From<$t> for BitSet {
+            fn from(value: $t) -> Self {
+                let mut bitset = BitSet::new(size_of::<$t>() * 8);
+
+                // the type can be smaller or larger than usize, depending on the machine,
+                // so each block takes the next block_size bits of the value
+                let block_size = Self::block_size();
+                for (block_number, block) in bitset.blocks.iter_mut().enumerate() {
+                    *block = (value >> (block_number * block_size)) as usize;
+                }
+                bitset
+            }
+        }
+    };
+}
+
+impl From<u8> for BitSet {
+    fn from(value: u8) -> Self {
+        // This implementation is simple. I don't care about the number of blocks here,
+        // as it's impossible to have something smaller than u8.
+        Self {
+            blocks: vec![usize::from(value)],
+            size: u8::BITS as usize,
+        }
+    }
+}
+
+add_from_uint_trait! {u16}
+add_from_uint_trait! {u32}
+add_from_uint_trait! {u64}
+add_from_uint_trait! {u128}
+add_from_uint_trait! {usize}
+
+/// The byte 0 holds the bits [0; 8), the byte 1 holds the bits [8; 16) and so on.
+/// The size of the bitset is the number of bits in all the bytes.
+///
+/// Panics:
+///    - when there are no bytes, as a BitSet with zero bits is not allowed
+///
+impl From<&[u8]> for BitSet {
+    fn from(value: &[u8]) -> Self {
+        let mut bitset = BitSet::new(value.len() * 8);
+        let bytes_per_block = size_of::<usize>();
+
+        for (byte_number, byte) in value.iter().enumerate() {
+            let shift = (byte_number % bytes_per_block) * 8;
+            bitset.blocks[byte_number / bytes_per_block] |= usize::from(*byte) << shift;
+        }
+        bitset
+    }
+}
+
+/// The same as the conversion from `&[u8]`.
+impl From<Vec<u8>> for BitSet {
+    fn from(value: Vec<u8>) -> Self {
+        BitSet::from(value.as_slice())
+    }
+}
+
+/// Creates a bitset with the bit `i` set to the `i`-th value.
+///
+/// Panics:
+///    - when there are no values, as the bitset can't have zero bits
+///
+impl FromIterator<bool> for BitSet {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut blocks = vec![];
+        let mut size = 0;
+        for value in iter {
+            let bit_position = Self::get_bit_position(size);
+            if bit_position.block_position == 0 {
+                blocks.push(0);
+            }
+            if value {
+                let bitmask = Self::make_bitmask(bit_position.block_position);
+                blocks[bit_position.block_number] |= bitmask;
+            }
+            size += 1;
+        }
+        if size == 0 {
+            panic!("Creating BitSet with zero bits is not allowed.");
+        }
+        BitSet { blocks, size }
+    }
+}
+
+/// The same as collecting the values into a bitset.
+///
+/// Panics:
+///    - when the slice is empty
+///
+impl From<&[bool]> for BitSet {
+    fn from(value: &[bool]) -> Self {
+        value.iter().copied().collect()
+    }
+}
+
+/// The same as collecting the values into a bitset.
+///
+/// Panics:
+///    - when N=0
+///
+impl<const N: usize> From<[bool; N]> for BitSet {
+    fn from(value: [bool; N]) -> Self {
+        BitSet::from(&value[..])
+    }
+}
+
+// Conversion functions
+impl BitSet {
+    /// Returns the bits as bytes, the byte 0 holds the bits [0; 8) and so on.
+    ///
+    /// There are `ceil(size / 8)` bytes, the bits of the last one,
+    /// which are outside the size, are set to false.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self
+            .blocks
+            .iter()
+            .flat_map(|block| block.to_le_bytes())
+            .collect();
+        bytes.truncate(self.size.div_ceil(8));
+        bytes
+    }
+
+    /// Writes the size as a little endian u64, followed by the bytes from `to_bytes()`.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&(self.size as u64).to_le_bytes())?;
+        w.write_all(&self.to_bytes())
+    }
+
+    /// Reads a bitset written with `write_to()`.
+    ///
+    /// The error is returned when
+    ///    - the input is truncated
+    ///    - the size is zero or too large
+    ///    - there are bits set outside the size
+    ///
+    pub fn read_from<R: Read>(r: &mut R) -> io::Result<BitSet> {
+        let mut size_bytes = [0; 8];
+        r.read_exact(&mut size_bytes)?;
+        let size = match usize::try_from(u64::from_le_bytes(size_bytes)) {
+            Ok(0) | Err(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Invalid BitSet size.",
+                ))
+            }
+            Ok(size) => size,
+        };
+
+        // the size comes from the input, so the buffer grows only with the bytes really read
+        let bytes_number = size.div_ceil(8);
+        let mut bytes = vec![];
+        r.take(bytes_number as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != bytes_number {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "BitSet data is truncated.",
+            ));
+        }
+
+        let mut bitset = BitSet::from(bytes);
+        if bitset.count() != bitset.rank(size) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "BitSet has bits set outside the size.",
+            ));
+        }
+        bitset.truncate(size);
+        Ok(bitset)
+    }
+
+    /// Returns the bits as a string like `to_string()`, with the separator
+    /// inserted between each group of `group_size` bits.
+    ///
+    /// The groups are counted from the lowest bit, so the top group may be shorter.
+    ///
+    /// Panics:
+    ///    - when group_size=0
+    ///
+    pub fn to_string_grouped(&self, group_size: usize, separator: char) -> String {
+        if group_size == 0 {
+            panic!("Grouping bits with zero group size is not allowed.");
+        }
+        let bits = self.to_string();
+        let mut res = String::with_capacity(bits.len() + bits.len() / group_size);
+        for (i, bit) in bits.chars().enumerate() {
+            if i != 0 && (bits.len() - i).is_multiple_of(group_size) {
+                res.push(separator);
+            }
+            res.push(bit);
+        }
+        res
+    }
+
+    /// Returns the bits as a string with the bit 0 first, so reversed `to_string()`.
+    pub fn to_string_lsb_first(&self) -> String {
+        (0..self.size)
+            .map(|position| if self.get(position) { '1' } else { '0' })
+            .collect()
+    }
+
+    /// Returns the bits as a hexadecimal number, with the highest digit first.
+    ///
+    /// There is a digit for each 4 bits, so the highest digit can hold fewer bits.
+    pub fn to_hex(&self) -> String {
+        let digits = self.size.div_ceil(4);
+        let mut res = String::with_capacity(digits);
+        for digit_number in (0..digits).rev() {
+            // the digits never cross the blocks, and the unused bits are false
+            let digit = self.get_bits(digit_number * 4, 4) as u32;
+            res.push(std::char::from_digit(digit, 16).unwrap());
+        }
+        res
+    }
+
+    /// Parses the hexadecimal number, like the one created by `to_hex()`,
+    /// into a bitset of the given size.
+    ///
+    /// The error is returned when
+    ///    - the string is empty or contains a digit not valid for the radix 16
+    ///    - the string has more digits than `to_hex()` would create for the size
+    ///    - the value doesn't fit in the size
+    ///
+    /// Panics:
+    ///    - when size=0
+    ///
+    pub fn from_hex(size: usize, s: &str) -> Result<Self, BitSetError> {
+        if size == 0 {
+            panic!("Creating BitSet with zero bits is not allowed.");
+        }
+        if s.chars().count() > size.div_ceil(4) {
+            return Err(BitSetError::OverflowError { size });
+        }
+        Self::from_str_radix(s, 16, size)
+    }
+
+    /// Parses the string with the number in the given radix into a bitset of the given size.
+    ///
+    /// The string is read as a number, so the last digit holds the lowest bits.
+    /// The supported radixes are 2, 8 and 16.
+    ///
+    /// The error is returned when
+    ///    - the radix is not supported
+    ///    - the string is empty or contains a digit not valid for the radix
+    ///    - the value doesn't fit in the size
+    ///
+    /// Panics:
+    ///    - when size=0
+    ///
+    pub fn from_str_radix(s: &str, radix: u32, size: usize) -> Result<Self, BitSetError> {
+        let mut bitset = BitSet::new(size);
+        let bits_per_digit = match radix {
+            2 => 1,
+            8 => 3,
+            16 => 4,
+            _ => return Err(BitSetError::RadixError { radix }),
+        };
+        if s.is_empty() {
+            return Err(BitSetError::EmptyStringError);
+        }
+
+        for (digit_number, digit) in s.chars().rev().enumerate() {
+            let value = match digit.to_digit(radix) {
+                Some(value) => value,
+                None => return Err(BitSetError::DigitError { digit, radix }),
+            };
+            for bit in 0..bits_per_digit {
+                if value & (1 << bit) == 0 {
+                    continue;
+                }
+                let position = digit_number * bits_per_digit + bit;
+                if position >= size {
+                    return Err(BitSetError::OverflowError { size });
+                }
+                bitset.set(position, true);
+            }
+        }
+        Ok(bitset)
+    }
+}
+
+impl From<&BitSet> for BitSet {
+    fn from(value: &BitSet) -> Self {
+        value.clone()
+    }
+}
 
 impl fmt::Display for BitSet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut res = String::with_capacity(Self::block_size() * self.blocks.len());
         let block_size = Self::block_size();
-        for block in (&self.blocks).iter().rev() {
-            res += &format!("{:0width$b}", block, width = block_size);
+        for block in self.blocks.iter().rev() {
+            res += &format!("{:0width$b}", block, width = block_size);
+        }
+        res = res[(res.len() - self.size)..].to_string();
+        write!(f, "{}", res)
+    }
+}
+
+macro_rules! add_try_from_uint_trait {
+    ($t:ty) => {
+        impl TryFrom<BitSet> for $t {
+            type Error = &'static str;
+
+            fn try_from(value: BitSet) -> Result<Self, Self::Error> {
+                let output_bits = size_of::<$t>() * 8;
+                let block_size = BitSet::block_size();
+
+                // the type can be smaller or larger than usize, depending on the machine,
+                // so each block is moved to its position in the output
+                let mut output: $t = 0;
+                for (block_number, block) in value.blocks.iter().enumerate() {
+                    if *block == 0 {
+                        continue;
+                    }
+                    // if any bit is set above the output bits, then the conversion is not doable
+                    let position = block_number * block_size;
+                    if position >= output_bits
+                        || (output_bits - position < block_size
+                            && block >> (output_bits - position) != 0)
+                    {
+                        return Err("Value stored in BitSet cannot be converted to u8.");
+                    }
+                    output |= (*block as $t) << position;
+                }
+                Ok(output)
+            }
+        }
+    };
+}
+
+add_try_from_uint_trait! {u8}
+add_try_from_uint_trait! {u16}
+add_try_from_uint_trait! {u32}
+add_try_from_uint_trait! {u64}
+add_try_from_uint_trait! {u128}
+add_try_from_uint_trait! {usize}
+
+macro_rules! add_saturating_into_uint_function {
+    ($name:ident, $t:ty) => {
+        impl BitSet {
+            /// Converts the bitset to the number type,
+            /// returning the type's MAX when the stored value is too large for it.
+            pub fn $name(&self) -> $t {
+                let output_bits = size_of::<$t>() * 8;
+                match self.find_last_set() {
+                    Some(position) if position >= output_bits => return <$t>::MAX,
+                    None => return 0,
+                    _ => (),
+                }
+
+                // all the bits fit now, so the blocks above the output bits are empty
+                let block_size = BitSet::block_size();
+                let mut output: $t = 0;
+                for (block_number, block) in self.blocks.iter().enumerate() {
+                    let position = block_number * block_size;
+                    if position >= output_bits {
+                        break;
+                    }
+                    output |= (*block as $t) << position;
+                }
+                output
+            }
+        }
+    };
+}
+
+add_saturating_into_uint_function! {saturating_into_u8, u8}
+add_saturating_into_uint_function! {saturating_into_u16, u16}
+add_saturating_into_uint_function! {saturating_into_u32, u32}
+add_saturating_into_uint_function! {saturating_into_u64, u64}
+add_saturating_into_uint_function! {saturating_into_u128, u128}
+add_saturating_into_uint_function! {saturating_into_usize, usize}
+
+/// Compares the bits with the bits of the number, the bit `i` with `(number >> i) & 1`.
+///
+/// The sizes don't need to match, the missing bits on either side are treated as false.
+/// So the bitset is not equal, if it has any bit set above the number type width.
+macro_rules! add_partial_eq_uint_trait {
+    ($t:ty) => {
+        impl PartialEq<$t> for BitSet {
+            fn eq(&self, other: &$t) -> bool {
+                let value = *other as u128;
+                let block_size = Self::block_size();
+                let stored_bits = self.blocks.len() * block_size;
+
+                // the number bits above the blocks can't be stored
+                if stored_bits < 128 && value >> stored_bits != 0 {
+                    return false;
+                }
+                self.blocks.iter().enumerate().all(|(block_number, block)| {
+                    let shift = block_number * block_size;
+                    let expected = if shift < 128 {
+                        (value >> shift) as usize
+                    } else {
+                        0
+                    };
+                    *block == expected
+                })
+            }
+        }
+    };
+}
+
+add_partial_eq_uint_trait! {u32}
+add_partial_eq_uint_trait! {u64}
+add_partial_eq_uint_trait! {u128}
+add_partial_eq_uint_trait! {usize}
+
+/// The serialized bitset contains the size and the list of blocks.
+#[cfg(feature = "serde")]
+mod serialization {
+    use super::BitSet;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    #[serde(rename = "BitSet")]
+    struct SerializedBitSet<'a> {
+        size: usize,
+        blocks: &'a [usize],
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename = "BitSet")]
+    struct DeserializedBitSet {
+        size: usize,
+        blocks: Vec<usize>,
+    }
+
+    impl Serialize for BitSet {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            SerializedBitSet {
+                size: self.size,
+                blocks: &self.blocks,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for BitSet {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let DeserializedBitSet { size, blocks } =
+                DeserializedBitSet::deserialize(deserializer)?;
+
+            if size == 0 {
+                return Err(D::Error::custom("BitSet with zero bits is not allowed."));
+            }
+            if blocks.len() != BitSet::blocks_number(size) {
+                return Err(D::Error::custom(format!(
+                    "BitSet with {} bits needs {} blocks, got {}.",
+                    size,
+                    BitSet::blocks_number(size),
+                    blocks.len()
+                )));
+            }
+            let bitset = BitSet { blocks, size };
+            if bitset.blocks[bitset.blocks.len() - 1] & !bitset.last_block_bitmask() != 0 {
+                return Err(D::Error::custom(format!(
+                    "BitSet has bits set outside the size {}.",
+                    size
+                )));
+            }
+            Ok(bitset)
+        }
+    }
+
+    #[cfg(test)]
+    mod test_serialization {
+        use super::super::test_helpers::{make_bitset, SIZES};
+        use super::*;
+
+        #[quickcheck]
+        fn check_json_round_trip(value: u128) -> bool {
+            for size in SIZES {
+                let b = make_bitset(*size, value);
+                let json = serde_json::to_string(&b).unwrap();
+                let c: BitSet = serde_json::from_str(&json).unwrap();
+                assert_eq!(c, b);
+            }
+            true
+        }
+
+        #[test]
+        fn check_json_format() {
+            let mut b = BitSet::new(3);
+            b.set(0, true);
+            b.set(2, true);
+            assert_eq!(
+                serde_json::to_string(&b).unwrap(),
+                r#"{"size":3,"blocks":[5]}"#
+            );
+        }
+
+        #[test]
+        fn check_rejecting_malformed_json() {
+            let error = serde_json::from_str::<BitSet>(r#"{"size":3,"blocks":[5,0]}"#)
+                .unwrap_err()
+                .to_string();
+            assert!(error.starts_with("BitSet with 3 bits needs 1 blocks, got 2."));
+
+            let error = serde_json::from_str::<BitSet>(r#"{"size":3,"blocks":[]}"#)
+                .unwrap_err()
+                .to_string();
+            assert!(error.starts_with("BitSet with 3 bits needs 1 blocks, got 0."));
+
+            let error = serde_json::from_str::<BitSet>(r#"{"size":3,"blocks":[8]}"#)
+                .unwrap_err()
+                .to_string();
+            assert!(error.starts_with("BitSet has bits set outside the size 3."));
+
+            let error = serde_json::from_str::<BitSet>(r#"{"size":0,"blocks":[]}"#)
+                .unwrap_err()
+                .to_string();
+            assert!(error.starts_with("BitSet with zero bits is not allowed."));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_helpers {
+    use super::*;
+
+    /// Makes a bitset of the given size, repeating the bits of the value.
+    pub fn make_bitset(size: usize, value: u128) -> BitSet {
+        let mut b = BitSet::new(size);
+        for i in 0..size {
+            b.set(i, value & (1 << (i % 128)) != 0);
+        }
+        b
+    }
+
+    /// The sizes for the tests: one bit, the sizes around the block boundaries,
+    /// and the ones spanning a couple of blocks.
+    pub const SIZES: &[usize] = &[1, 7, 63, 64, 65, 70, 128, 200];
+}
+
+#[cfg(test)]
+mod test_private_functions {
+
+    use super::*;
+
+    #[test]
+    fn check_getting_number_of_blocks() {
+        let block_size = usize::BITS as usize;
+
+        assert_eq!(BitSet::blocks_number(1), 1);
+        assert_eq!(BitSet::blocks_number(10), 1);
+
+        assert_eq!(BitSet::blocks_number(block_size), 1);
+        assert_eq!(BitSet::blocks_number(block_size + 1), 2);
+
+        assert_eq!(BitSet::blocks_number(2 * block_size), 2);
+        assert_eq!(BitSet::blocks_number(2 * block_size + 1), 3);
+    }
+
+    #[test]
+    fn check_getting_number_of_bits_in_block() {
+        assert_eq!(BitSet::block_size(), usize::BITS as usize);
+    }
+}
+
+#[cfg(test)]
+mod test_constructors {
+
+    use super::test_helpers::SIZES;
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
+    fn check_creating_bitset_with_zero_bits() {
+        BitSet::new(0);
+    }
+
+    #[test]
+    fn check_creating_new_bitset() {
+        let block_size = usize::BITS as usize;
+
+        // for 1 bit we should have 1 block
+        let a = BitSet::new(1);
+        assert_eq!(a.blocks.len(), 1);
+        assert_eq!(a.size, 1);
+
+        // for max bits in one block, we should have one block
+        let b = BitSet::new(block_size);
+        assert_eq!(b.blocks.len(), 1);
+        assert_eq!(b.size, block_size);
+
+        // for 1 + max bits in one block, we should have two block
+        let c = BitSet::new(block_size + 1);
+        assert_eq!(c.blocks.len(), 2);
+        assert_eq!(c.size, block_size + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
+    fn check_creating_checkerboard_with_zero_bits() {
+        BitSet::checkerboard(0, true);
+    }
+
+    #[test]
+    fn check_creating_checkerboard() {
+        for size in SIZES {
+            let a = BitSet::checkerboard(*size, true);
+            let b = BitSet::checkerboard(*size, false);
+            assert_eq!(a.len(), *size);
+            assert_eq!(b.len(), *size);
+            for i in 0..*size {
+                assert_eq!(a.get(i), i % 2 == 0);
+                assert_eq!(b.get(i), i % 2 == 1);
+            }
+
+            // one is the negation of the other
+            let mut c = a.clone();
+            c.negate();
+            assert_eq!(c, b);
+        }
+        assert_eq!(BitSet::checkerboard(8, true).to_string(), "01010101");
+    }
+
+    #[test]
+    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
+    fn check_creating_bitset_with_capacity_and_zero_bits() {
+        BitSet::with_capacity(0, 100);
+    }
+
+    #[test]
+    fn check_creating_bitset_with_capacity() {
+        let block_size = usize::BITS as usize;
+
+        let mut a = BitSet::with_capacity(10, 4 * block_size);
+        assert_eq!(a.len(), 10);
+        assert_eq!(a.blocks.len(), 1);
+        assert!(a.capacity() >= 4 * block_size);
+        assert!(a.is_empty());
+
+        // growing up to the capacity doesn't reallocate
+        let pointer = a.blocks.as_ptr();
+        a.resize_with(4 * block_size, |_| true);
+        assert_eq!(a.blocks.as_ptr(), pointer);
+
+        // capacity smaller than size is ignored
+        let b = BitSet::with_capacity(block_size + 1, 1);
+        assert_eq!(b.len(), block_size + 1);
+        assert_eq!(b.blocks.len(), 2);
+        assert!(b.capacity() >= 2 * block_size);
+    }
+
+    #[test]
+    fn check_creating_filled_bitset() {
+        let b = BitSet::new_filled(70, true);
+        assert_eq!(b.count(), 70);
+        assert!(b.all());
+
+        for size in SIZES {
+            let mut expected = BitSet::new(*size);
+            assert_eq!(BitSet::new_filled(*size, false), expected);
+            expected.set_all(true);
+            assert_eq!(BitSet::new_filled(*size, true), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
+    fn check_creating_filled_bitset_with_zero_bits() {
+        BitSet::new_filled(0, true);
+    }
+
+    #[test]
+    fn check_creating_default_bitset() {
+        let b = BitSet::default();
+        assert_eq!(b.len(), 1);
+        assert!(b.is_empty());
+        assert_eq!(b, BitSet::new(1));
+
+        #[derive(Default)]
+        struct WithBitSet {
+            bits: BitSet,
+        }
+        assert_eq!(WithBitSet::default().bits.len(), 1);
+    }
+
+    #[test]
+    fn check_creating_bitset_from_indices() {
+        for size in SIZES {
+            let indices: Vec<usize> = (0..*size).filter(|i| i % 5 == 0).collect();
+            let mut expected = BitSet::new(*size);
+            for i in &indices {
+                expected.set(*i, true);
+            }
+            assert_eq!(BitSet::from_indices(*size, indices.clone()), expected);
+
+            // repeated positions are set only once
+            let repeated = indices.iter().chain(indices.iter()).copied();
+            assert_eq!(BitSet::from_indices(*size, repeated), expected);
+        }
+        assert_eq!(BitSet::from_indices(10, vec![]), BitSet::new(10));
+    }
+
+    #[test]
+    fn check_creating_bitset_from_range() {
+        let b = BitSet::from_range(100, 10..20);
+        assert_eq!(b.size, 100);
+        assert_eq!(b.collect_ones(), (10..20).collect::<Vec<_>>());
+
+        let b = BitSet::from_range(200, 60..130);
+        assert_eq!(b.count(), 70);
+        assert_eq!(b.min(), Some(60));
+        assert_eq!(b.max(), Some(129));
+
+        assert_eq!(BitSet::from_range(70, 0..70), BitSet::new_filled(70, true));
+        assert!(BitSet::from_range(70, 20..20).is_empty());
+        assert!(BitSet::from_range(70, 70..70).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit range [10, 71) is outside available range: [0, 69]")]
+    fn check_creating_bitset_from_range_outside_size() {
+        BitSet::from_range(70, 10..71);
+    }
+
+    #[test]
+    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
+    fn check_creating_bitset_from_range_with_zero_bits() {
+        BitSet::from_range(0, 0..0);
+    }
+
+    #[test]
+    fn check_builder() {
+        let built = BitSet::builder(70)
+            .set(3)
+            .set(7)
+            .range(10..12)
+            .set(69)
+            .build();
+
+        let mut expected = BitSet::new(70);
+        expected.set(3, true);
+        expected.set(7, true);
+        expected.set(10, true);
+        expected.set(11, true);
+        expected.set(69, true);
+
+        assert_eq!(built, expected);
+        assert!(BitSet::builder(70).build().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
+    fn check_builder_with_zero_bits() {
+        BitSet::builder(0).build();
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit position [70] is outside available range: [0, 69]")]
+    fn check_builder_with_position_outside_size() {
+        BitSet::builder(70).set(3).set(70).build();
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit range [60, 71) is outside available range: [0, 69]")]
+    fn check_builder_with_range_outside_size() {
+        BitSet::builder(70).range(60..71).build();
+    }
+
+    #[test]
+    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
+    fn check_creating_bitset_from_indices_with_zero_bits() {
+        BitSet::from_indices(0, vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit position [70] is outside available range: [0, 69]")]
+    fn check_creating_bitset_from_indices_outside_range() {
+        BitSet::from_indices(70, vec![0, 70]);
+    }
+}
+
+#[cfg(test)]
+#[macro_use]
+mod test_conversions_to_types {
+
+    use super::test_helpers::SIZES;
+    use super::*;
+
+    #[test]
+    fn check_string_conversion() {
+        let b = BitSet::new(1);
+        assert_eq!(b.to_string(), "0");
+
+        let mut c = BitSet::new(1);
+        c.set(0, true);
+        assert_eq!(c.to_string(), "1");
+
+        let mut d = BitSet::new(66);
+        d.set(0, true);
+        d.set(5, true);
+        d.set(64, true);
+        //                    60        50        40        30        20        10         0
+        //                543210987654321098765432109876543210987654321098765432109876543210
+        let expected_d = "010000000000000000000000000000000000000000000000000000000000100001";
+        assert_eq!(d.to_string(), expected_d);
+    }
+
+    #[test]
+    fn check_lsb_first_string_conversion() {
+        let mut b = BitSet::new(4);
+        b.set(0, true);
+        assert_eq!(b.to_string(), "0001");
+        assert_eq!(b.to_string_lsb_first(), "1000");
+
+        let b = BitSet::from(0b0100_0011_u8);
+        assert_eq!(b.to_string_lsb_first(), "11000010");
+    }
+
+    #[quickcheck]
+    fn check_lsb_first_string_conversion_for_random_values(value: u128) -> bool {
+        for size in SIZES {
+            let b = super::test_helpers::make_bitset(*size, value);
+            let reversed: String = b.to_string().chars().rev().collect();
+            assert_eq!(b.to_string_lsb_first(), reversed);
+        }
+        true
+    }
+
+    #[test]
+    #[should_panic(expected = "Grouping bits with zero group size is not allowed.")]
+    fn check_grouped_string_conversion_with_zero_group_size() {
+        BitSet::new(8).to_string_grouped(0, '_');
+    }
+
+    #[test]
+    fn check_grouped_string_conversion() {
+        let b = BitSet::from(0b0100_0001_u8);
+        assert_eq!(b.to_string_grouped(4, '_'), "0100_0001");
+        assert_eq!(b.to_string_grouped(8, ' '), "01000001");
+        assert_eq!(b.to_string_grouped(1, ','), "0,1,0,0,0,0,0,1");
+        assert_eq!(b.to_string_grouped(100, '_'), "01000001");
+
+        // the top group is shorter
+        let mut b = BitSet::new(10);
+        b.set(0, true);
+        b.set(9, true);
+        assert_eq!(b.to_string_grouped(4, '_'), "10_0000_0001");
+        assert_eq!(b.to_string_grouped(8, ' '), "10 00000001");
+
+        let b = BitSet::from(u16::MAX);
+        assert_eq!(b.to_string_grouped(8, '.'), "11111111.11111111");
+    }
+
+    #[quickcheck]
+    fn check_grouped_string_conversion_for_random_values(value: u128, group_size: u8) -> bool {
+        let group_size = group_size as usize % 20 + 1;
+        for size in SIZES {
+            let b = super::test_helpers::make_bitset(*size, value);
+            let grouped = b.to_string_grouped(group_size, '_');
+            assert_eq!(grouped.replace('_', ""), b.to_string());
+
+            // all the groups have the full size, but the top one
+            let groups: Vec<&str> = grouped.split('_').collect();
+            assert_eq!(groups.len(), size.div_ceil(group_size));
+            assert!(groups[1..].iter().all(|group| group.len() == group_size));
+            assert!(!groups[0].is_empty() && groups[0].len() <= group_size);
+        }
+        true
+    }
+
+    #[test]
+    fn check_comparing_with_numbers() {
+        let b = BitSet::from(170u8);
+        assert_eq!(b, 170u64);
+        assert_eq!(b, 170u32);
+        assert_eq!(b, 170u128);
+        assert_eq!(b, 170usize);
+        assert_ne!(b, 171u64);
+        // the bits above the size are never set
+        assert_ne!(b, 170u64 | 1 << 8);
+
+        let mut b = BitSet::new(200);
+        assert_eq!(b, 0u64);
+        b.set(1, true);
+        b.set(127, true);
+        assert_eq!(b, 1u128 << 127 | 2);
+        assert_ne!(b, 2u64);
+
+        // the bits above the number width must be false
+        b.set(150, true);
+        assert_ne!(b, 1u128 << 127 | 2);
+    }
+
+    #[quickcheck]
+    fn check_comparing_with_random_numbers(value: u64) -> bool {
+        for size in SIZES {
+            let mut b = BitSet::new(*size);
+            for i in 0..min(*size, 64) {
+                b.set(i, value & (1 << i) != 0);
+            }
+            let expected = *size >= 64 || value >> size == 0;
+            assert_eq!(b == value, expected);
+            assert_eq!(b == value as u128, expected);
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_conversion_of_large_numbers(high: u64, low: u64) -> bool {
+        // quickcheck generates rather small numbers, so the high bits are set explicitly
+        let value = (high as u128) << 64 | low as u128;
+        let b = BitSet::from(value);
+        assert_eq!(b.size, 128);
+        assert_eq!(b.blocks.len(), BitSet::blocks_number(128));
+        assert_eq!(b.to_u64_vec(), vec![low, high]);
+        assert_eq!(u128::try_from(b.clone()), Ok(value));
+        assert_eq!(u64::try_from(b).is_ok(), high == 0);
+
+        let b = BitSet::from(low);
+        assert_eq!(b.size, 64);
+        assert_eq!(b.blocks.len(), BitSet::blocks_number(64));
+        assert_eq!(b.to_u64_vec(), vec![low]);
+        assert_eq!(u64::try_from(b.clone()), Ok(low));
+        assert_eq!(u32::try_from(b).is_ok(), low >> 32 == 0);
+        true
+    }
+
+    #[test]
+    fn check_conversion_of_bitsets_larger_than_the_type() {
+        let mut b = BitSet::new(200);
+        b.set(3, true);
+        assert_eq!(u8::try_from(b.clone()), Ok(8));
+        assert_eq!(u128::try_from(b.clone()), Ok(8));
+
+        // the bits above the type are set
+        b.set(150, true);
+        assert!(u8::try_from(b.clone()).is_err());
+        assert!(u128::try_from(b.clone()).is_err());
+
+        b.set(150, false);
+        b.set(127, true);
+        assert!(u64::try_from(b.clone()).is_err());
+        assert_eq!(u128::try_from(b), Ok(1 << 127 | 8));
+    }
+
+    /// Checks conversion from different values
+    macro_rules! check_type_conversion {
+        ($func:ident, $from:ty, $to:ty) => {
+            #[quickcheck]
+            fn $func(value: $from) -> bool {
+                let bitset = BitSet::from(value);
+
+                let new_value = <$to>::try_from(bitset);
+                if value as $from <= <$to>::MAX as $from {
+                    // conversion should be fine
+                    assert_eq!(value as $to, new_value.unwrap());
+                } else {
+                    // in this case the value is too large
+                    assert_eq!(
+                        new_value,
+                        Err("Value stored in BitSet cannot be converted to u8.")
+                    );
+                }
+                true
+            }
+        };
+    }
+
+    check_type_conversion! {check_conversion_from_u8_to_u8, u8, u8}
+    check_type_conversion! {check_conversion_from_u16_to_u8, u16, u8}
+    check_type_conversion! {check_conversion_from_u32_to_u8, u32, u8}
+    check_type_conversion! {check_conversion_from_u64_to_u8, u64, u8}
+    check_type_conversion! {check_conversion_from_u128_to_u8, u128, u8}
+    check_type_conversion! {check_conversion_from_usize_to_u8, usize, u8}
+
+    check_type_conversion! {check_conversion_from_u8_to_u16, u8, u16}
+    check_type_conversion! {check_conversion_from_u16_to_u16, u16, u16}
+    check_type_conversion! {check_conversion_from_u32_to_u16, u32, u16}
+    check_type_conversion! {check_conversion_from_u64_to_u16, u64, u16}
+    check_type_conversion! {check_conversion_from_u128_to_u16, u128, u16}
+    check_type_conversion! {check_conversion_from_usize_to_u16, usize, u16}
+
+    check_type_conversion! {check_conversion_from_u8_to_u32, u8, u32}
+    check_type_conversion! {check_conversion_from_u16_to_u32, u16, u32}
+    check_type_conversion! {check_conversion_from_u32_to_u32, u32, u32}
+    check_type_conversion! {check_conversion_from_u64_to_u32, u64, u32}
+    check_type_conversion! {check_conversion_from_u128_to_u32, u128, u32}
+    check_type_conversion! {check_conversion_from_usize_to_u32, usize, u32}
+
+    check_type_conversion! {check_conversion_from_u8_to_u64, u8, u64}
+    check_type_conversion! {check_conversion_from_u16_to_u64, u16, u64}
+    check_type_conversion! {check_conversion_from_u32_to_u64, u32, u64}
+    check_type_conversion! {check_conversion_from_u64_to_u64, u64, u64}
+    check_type_conversion! {check_conversion_from_u128_to_u64, u128, u64}
+    check_type_conversion! {check_conversion_from_usize_to_u64, usize, u64}
+
+    check_type_conversion! {check_conversion_from_u8_to_u128, u8, u128}
+    check_type_conversion! {check_conversion_from_u16_to_u128, u16, u128}
+    check_type_conversion! {check_conversion_from_u32_to_u128, u32, u128}
+    check_type_conversion! {check_conversion_from_u64_to_u128, u64, u128}
+    check_type_conversion! {check_conversion_from_u128_to_u128, u128, u128}
+    check_type_conversion! {check_conversion_from_usize_to_u128, usize, u128}
+
+    check_type_conversion! {check_conversion_from_u8_to_usize, u8, usize}
+    check_type_conversion! {check_conversion_from_u16_to_usize, u16, usize}
+    check_type_conversion! {check_conversion_from_u32_to_usize, u32, usize}
+    check_type_conversion! {check_conversion_from_u64_to_usize, u64, usize}
+    check_type_conversion! {check_conversion_from_u128_to_usize, u128, usize}
+    check_type_conversion! {check_conversion_from_usize_to_usize, usize, usize}
+
+    #[test]
+    fn check_saturating_conversions() {
+        let mut b = BitSet::new(200);
+        assert_eq!(b.saturating_into_u8(), 0);
+        assert_eq!(b.saturating_into_u128(), 0);
+
+        b.set(3, true);
+        b.set(7, true);
+        assert_eq!(b.saturating_into_u8(), 136);
+        assert_eq!(b.saturating_into_u64(), 136);
+
+        b.set(8, true);
+        assert_eq!(b.saturating_into_u8(), u8::MAX);
+        assert_eq!(b.saturating_into_u16(), 392);
+
+        b.set(127, true);
+        assert_eq!(b.saturating_into_u64(), u64::MAX);
+        assert_eq!(b.saturating_into_u128(), 1 << 127 | 392);
+
+        b.set(128, true);
+        assert_eq!(b.saturating_into_u128(), u128::MAX);
+        assert_eq!(b.saturating_into_usize(), usize::MAX);
+    }
+
+    /// Checks the saturating conversion of different values
+    macro_rules! check_saturating_conversion {
+        ($func:ident, $from:ty, $to:ty, $method:ident) => {
+            #[quickcheck]
+            fn $func(value: $from) -> bool {
+                let bitset = BitSet::from(value);
+
+                let expected = if value as u128 <= <$to>::MAX as u128 {
+                    value as $to
+                } else {
+                    <$to>::MAX
+                };
+                assert_eq!(bitset.$method(), expected);
+                true
+            }
+        };
+    }
+
+    check_saturating_conversion! {check_saturating_from_u16_to_u8, u16, u8, saturating_into_u8}
+    check_saturating_conversion! {check_saturating_from_u64_to_u16, u64, u16, saturating_into_u16}
+    check_saturating_conversion! {check_saturating_from_u64_to_u32, u64, u32, saturating_into_u32}
+    check_saturating_conversion! {check_saturating_from_u128_to_u64, u128, u64, saturating_into_u64}
+    check_saturating_conversion! {check_saturating_from_u8_to_u64, u8, u64, saturating_into_u64}
+    check_saturating_conversion! {check_saturating_from_u128_to_usize, u128, usize, saturating_into_usize}
+}
+
+#[cfg(test)]
+#[macro_use]
+mod test_conversions_from_types {
+
+    use super::test_helpers::make_bitset;
+    use super::*;
+
+    /// Checks conversion from different values
+    macro_rules! check_type_conversion {
+        ($func:ident, $t:ty) => {
+            #[quickcheck]
+            fn $func(value: $t) -> bool {
+                let bitset = BitSet::from(value);
+                let value_bits_count = size_of::<$t>() * 8;
+
+                // the bitset should have the same number of bits as the initial value
+                assert_eq!(bitset.size, value_bits_count);
+
+                // converting both to a string should give the same result
+                let value_bits = format!("{:0width$b}", value, width = value_bits_count);
+                let bitset_bits = bitset.to_string();
+                assert_eq! {value_bits, bitset_bits}
+
+                // also, check bit by bit that everything is the same
+                for bit in 0..value_bits_count - 1 {
+                    let bit_from_value: bool = (value & (1 << bit) != 0);
+                    assert_eq! {bit_from_value, bitset.get(bit)}
+                }
+                true
+            }
+        };
+    }
+
+    #[test]
+    fn check_conversion_from_u8_value() {
+        let b = BitSet::from(0_u8);
+        assert_eq!(b.size, 8);
+        assert_eq!(b.blocks.len(), 1);
+        assert_eq!(b.to_string(), "00000000");
+
+        let b = BitSet::from(u8::MAX);
+        assert_eq!(b.size, 8);
+        assert_eq!(b.blocks.len(), 1);
+        assert_eq!(b.to_string(), "11111111");
+
+        let b = BitSet::from(170_u8);
+        assert_eq!(b.size, 8);
+        assert_eq!(b.blocks.len(), 1);
+        assert_eq!(b.to_string(), "10101010");
+    }
+
+    #[test]
+    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
+    fn check_conversion_from_empty_bytes() {
+        let _ = BitSet::from(Vec::<u8>::new());
+    }
+
+    #[test]
+    fn check_conversion_from_bytes() {
+        let b = BitSet::from(vec![0b0000_0001, 0b1000_0000, 0b0101_0000]);
+        assert_eq!(b.size, 24);
+        assert_eq!(b.to_string(), "010100001000000000000001");
+
+        let bytes: &[u8] = &[0xff; 9];
+        let b = BitSet::from(bytes);
+        assert_eq!(b.size, 72);
+        assert_eq!(b.blocks.len(), BitSet::blocks_number(72));
+        assert_eq!(b.count(), 72);
+    }
+
+    #[quickcheck]
+    fn check_conversion_from_random_bytes(bytes: Vec<u8>) -> bool {
+        if bytes.is_empty() {
+            return true;
+        }
+        let b = BitSet::from(bytes.clone());
+        assert_eq!(b.size, bytes.len() * 8);
+        for i in 0..b.size {
+            assert_eq!(b.get(i), bytes[i / 8] & (1 << (i % 8)) != 0);
+        }
+        true
+    }
+
+    #[test]
+    fn check_conversion_from_bools() {
+        let b = BitSet::from(&[true, false, true][..]);
+        assert_eq!(b.size, 3);
+        assert_eq!(b.to_string(), "101");
+
+        let b = BitSet::from([false, true, true, false]);
+        assert_eq!(b.size, 4);
+        assert_eq!(b.to_string(), "0110");
+    }
+
+    #[test]
+    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
+    fn check_conversion_from_empty_bools() {
+        let _ = BitSet::from(&[][..] as &[bool]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
+    fn check_collecting_no_bools() {
+        let _: BitSet = std::iter::empty().collect();
+    }
+
+    #[quickcheck]
+    fn check_conversion_from_random_bools(values: Vec<bool>) -> bool {
+        if values.is_empty() {
+            return true;
+        }
+        let b = BitSet::from(values.as_slice());
+        assert_eq!(b.size, values.len());
+        assert_eq!(b.blocks.len(), BitSet::blocks_number(values.len()));
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(b.get(i), *value);
+        }
+        assert_eq!(values.iter().copied().collect::<BitSet>(), b);
+        true
+    }
+
+    #[test]
+    fn check_conversion_from_bool_array_and_collecting() {
+        let values = [true; 70];
+        let b = BitSet::from(values);
+        assert_eq!(b, values.iter().copied().collect::<BitSet>());
+        assert!(b.all());
+        assert_eq!(b.count(), 70);
+    }
+
+    #[quickcheck]
+    fn check_conversion_to_bytes_and_back(bytes: Vec<u8>) -> bool {
+        if bytes.is_empty() {
+            return true;
+        }
+        let b = BitSet::from(bytes.clone());
+        assert_eq!(b.to_bytes(), bytes);
+        assert_eq!(BitSet::from(b.to_bytes()), b);
+        true
+    }
+
+    #[test]
+    fn check_conversion_to_bytes() {
+        let mut b = BitSet::new(16);
+        b.set(0, true);
+        b.set(15, true);
+        assert_eq!(b.to_bytes(), vec![0b0000_0001, 0b1000_0000]);
+
+        // the size is not a multiple of 8
+        let mut b = BitSet::new(70);
+        b.negate();
+        let mut expected = vec![0xff; 8];
+        expected.push(0b0011_1111);
+        assert_eq!(b.to_bytes(), expected);
+
+        let b = BitSet::new(1);
+        assert_eq!(b.to_bytes(), vec![0]);
+    }
+
+    #[test]
+    fn check_conversion_from_str_radix() {
+        let b = BitSet::from_str_radix("1011", 2, 6).unwrap();
+        assert_eq!(b.to_string(), "001011");
+
+        let b = BitSet::from_str_radix("17", 8, 6).unwrap();
+        assert_eq!(b.to_string(), "001111");
+
+        let b = BitSet::from_str_radix("aF", 16, 10).unwrap();
+        assert_eq!(b.to_string(), "0010101111");
+
+        // leading zeros don't need to fit in the size
+        let b = BitSet::from_str_radix("0001", 16, 1).unwrap();
+        assert_eq!(b.to_string(), "1");
+
+        let b = BitSet::from_str_radix("1", 16, 70).unwrap();
+        assert_eq!(b.to_string(), format!("{:070b}", 1));
+    }
+
+    #[quickcheck]
+    fn check_conversion_from_str_radix_for_random_values(value: u128) -> bool {
+        let expected = BitSet::from(value);
+        let bin = format!("{:b}", value);
+        let oct = format!("{:o}", value);
+        let hex = format!("{:x}", value);
+        assert_eq!(BitSet::from_str_radix(&bin, 2, 128), Ok(expected.clone()));
+        assert_eq!(BitSet::from_str_radix(&oct, 8, 128), Ok(expected.clone()));
+        assert_eq!(BitSet::from_str_radix(&hex, 16, 128), Ok(expected));
+        true
+    }
+
+    #[test]
+    fn check_conversion_from_str_radix_errors() {
+        assert_eq!(
+            BitSet::from_str_radix("10", 10, 8),
+            Err(BitSetError::RadixError { radix: 10 })
+        );
+        assert_eq!(
+            BitSet::from_str_radix("102", 2, 8),
+            Err(BitSetError::DigitError {
+                digit: '2',
+                radix: 2
+            })
+        );
+        assert_eq!(
+            BitSet::from_str_radix("8", 8, 8),
+            Err(BitSetError::DigitError {
+                digit: '8',
+                radix: 8
+            })
+        );
+        assert_eq!(
+            BitSet::from_str_radix("", 16, 8),
+            Err(BitSetError::EmptyStringError)
+        );
+
+        // overflow
+        assert_eq!(
+            BitSet::from_str_radix("100", 16, 8),
+            Err(BitSetError::OverflowError { size: 8 })
+        );
+        assert_eq!(
+            BitSet::from_str_radix("1000", 2, 3),
+            Err(BitSetError::OverflowError { size: 3 })
+        );
+        assert_eq!(
+            BitSet::from_str_radix("10", 8, 3),
+            Err(BitSetError::OverflowError { size: 3 })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
+    fn check_conversion_from_str_radix_with_zero_bits() {
+        let _ = BitSet::from_str_radix("", 2, 0);
+    }
+
+    #[test]
+    fn check_conversion_to_hex() {
+        let b = BitSet::from(0xa5u8);
+        assert_eq!(b.to_hex(), "a5");
+
+        let mut b = BitSet::new(12);
+        b.set(0, true);
+        b.set(11, true);
+        assert_eq!(b.to_hex(), "801");
+
+        let mut b = BitSet::new(65);
+        b.set(64, true);
+        b.set(4, true);
+        assert_eq!(b.to_hex(), "10000000000000010");
+
+        assert_eq!(BitSet::new(1).to_hex(), "0");
+    }
+
+    #[quickcheck]
+    fn check_conversion_to_hex_and_back(value: u128) -> bool {
+        for size in &[1, 7, 8, 12, 63, 64, 65, 70, 128, 200] {
+            let b = make_bitset(*size, value);
+            let hex = b.to_hex();
+            assert_eq!(hex.len(), size.div_ceil(4));
+            assert_eq!(BitSet::from_hex(*size, &hex), Ok(b));
+        }
+        true
+    }
+
+    #[test]
+    fn check_conversion_from_hex_errors() {
+        assert_eq!(BitSet::from_hex(8, "a5"), Ok(BitSet::from(0xa5u8)));
+        assert_eq!(BitSet::from_hex(12, "FFF").unwrap().count(), 12);
+        assert_eq!(
+            BitSet::from_hex(8, "g5"),
+            Err(BitSetError::DigitError {
+                digit: 'g',
+                radix: 16
+            })
+        );
+        assert_eq!(BitSet::from_hex(8, ""), Err(BitSetError::EmptyStringError));
+
+        // too many digits, even if they are zeros
+        assert_eq!(
+            BitSet::from_hex(8, "0a5"),
+            Err(BitSetError::OverflowError { size: 8 })
+        );
+        // the top digit holds just one bit
+        assert_eq!(
+            BitSet::from_hex(65, "20000000000000000"),
+            Err(BitSetError::OverflowError { size: 65 })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
+    fn check_conversion_from_hex_with_zero_bits() {
+        let _ = BitSet::from_hex(0, "a");
+    }
+
+    #[quickcheck]
+    fn check_writing_and_reading(value: u128) -> bool {
+        for size in &[1, 7, 8, 63, 64, 65, 70, 128, 200] {
+            let b = make_bitset(*size, value);
+            let mut buffer: Vec<u8> = vec![];
+            b.write_to(&mut buffer).unwrap();
+            assert_eq!(buffer.len(), 8 + size.div_ceil(8));
+            assert_eq!(buffer[..8], (*size as u64).to_le_bytes());
+
+            let c = BitSet::read_from(&mut buffer.as_slice()).unwrap();
+            assert_eq!(c, b);
+        }
+        true
+    }
+
+    #[test]
+    fn check_reading_invalid_input() {
+        let mut b = BitSet::new(70);
+        b.set(69, true);
+        let mut buffer: Vec<u8> = vec![];
+        b.write_to(&mut buffer).unwrap();
+
+        // truncated bytes
+        let error = BitSet::read_from(&mut &buffer[..buffer.len() - 1]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        // truncated size
+        let error = BitSet::read_from(&mut &buffer[..5]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        // huge size with a truncated payload, shouldn't allocate for the whole size
+        let mut huge = (1_u64 << 50).to_le_bytes().to_vec();
+        huge.extend_from_slice(&[0xFF; 8]);
+        let error = BitSet::read_from(&mut huge.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        // zero size
+        let error = BitSet::read_from(&mut &[0; 8][..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // a bit set outside the size
+        let last = buffer.len() - 1;
+        buffer[last] |= 0b1000_0000;
+        let error = BitSet::read_from(&mut buffer.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn check_conversion_from_bitset_reference() {
+        let mut a = BitSet::new(70);
+        a.set(3, true);
+        a.set(69, true);
+
+        let mut b = BitSet::from(&a);
+        assert_eq!(a, b);
+
+        let c: BitSet = (&a).into();
+        assert_eq!(a, c);
+
+        // the copy is independent
+        b.set(3, false);
+        assert!(a.get(3));
+        assert_ne!(a, b);
+    }
+
+    // // Test converting from different values;
+    check_type_conversion! {check_conversion_from_u8, u8}
+    check_type_conversion! {check_conversion_from_u16, u16}
+    check_type_conversion! {check_conversion_from_u32, u32}
+    check_type_conversion! {check_conversion_from_u64, u64}
+    check_type_conversion! {check_conversion_from_u128, u128}
+    check_type_conversion! {check_conversion_from_usize, usize}
+}
+
+#[cfg(test)]
+mod test_basic_getter_and_setter {
+    use super::test_helpers::{make_bitset, SIZES};
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Bit position [256] is outside available range: [0, 255]")]
+    fn check_using_setter_for_too_large_position() {
+        let mut b = BitSet::new(256);
+        b.set(256, false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit position [256] is outside available range: [0, 255]")]
+    fn check_using_getter_for_too_large_position() {
+        let b = BitSet::new(256);
+        b.get(256);
+    }
+
+    #[test]
+    fn check_contains_bit() {
+        let mut b = BitSet::new(70);
+        b.set(0, true);
+        b.set(69, true);
+
+        assert!(b.contains_bit(0));
+        assert!(!b.contains_bit(1));
+        assert!(b.contains_bit(69));
+        assert!(!b.contains_bit(70));
+        assert!(!b.contains_bit(128));
+        assert!(!b.contains_bit(usize::MAX));
+    }
+
+    #[test]
+    fn check_insert_and_remove() {
+        let mut b = BitSet::new(70);
+
+        assert!(b.insert(65));
+        assert!(b.get(65));
+        assert!(!b.insert(65));
+        assert!(b.get(65));
+        assert_eq!(b.count(), 1);
+
+        assert!(b.remove(65));
+        assert!(!b.get(65));
+        assert!(!b.remove(65));
+        assert!(!b.get(65));
+        assert!(b.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit position [70] is outside available range: [0, 69]")]
+    fn check_insert_for_too_large_position() {
+        BitSet::new(70).insert(70);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit position [70] is outside available range: [0, 69]")]
+    fn check_remove_for_too_large_position() {
+        BitSet::new(70).remove(70);
+    }
+
+    #[test]
+    fn check_simple_operations() {
+        let mut b = BitSet::new(4);
+        assert!(!b.get(0));
+        assert!(!b.get(1));
+        assert!(!b.get(2));
+        assert!(!b.get(3));
+
+        b.set(0, true);
+        assert!(b.get(0));
+        assert!(!b.get(1));
+        assert!(!b.get(2));
+        assert!(!b.get(3));
+
+        b.set(3, true);
+        assert!(b.get(0));
+        assert!(!b.get(1));
+        assert!(!b.get(2));
+        assert!(b.get(3));
+
+        b.set(1, false);
+        b.set(3, false);
+        assert!(b.get(0));
+        assert!(!b.get(1));
+        assert!(!b.get(2));
+        assert!(!b.get(3));
+    }
+
+    #[quickcheck]
+    fn check_unchecked_getter_and_setter(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let mut c = BitSet::new(*size);
+            for i in 0..*size {
+                unsafe {
+                    assert_eq!(b.get_unchecked(i), b.get(i));
+                    c.set_unchecked(i, b.get(i));
+                }
+            }
+            assert_eq!(c, b);
+
+            for i in 0..*size {
+                unsafe {
+                    c.set_unchecked(i, false);
+                }
+            }
+            assert!(c.is_empty());
+        }
+        true
+    }
+
+    #[test]
+    fn check_try_get() {
+        let mut b = BitSet::new(70);
+        b.set(65, true);
+
+        assert_eq!(b.try_get(0), Some(false));
+        assert_eq!(b.try_get(65), Some(true));
+        assert_eq!(b.try_get(69), Some(false));
+        assert_eq!(b.try_get(70), None);
+        assert_eq!(b.try_get(usize::MAX), None);
+    }
+
+    #[test]
+    fn check_try_set() {
+        let mut b = BitSet::new(70);
+
+        // the previous value is returned
+        assert_eq!(b.try_set(65, true), Some(false));
+        assert_eq!(b.try_set(65, true), Some(true));
+        assert_eq!(b.try_set(65, false), Some(true));
+        assert_eq!(b.try_set(69, true), Some(false));
+        assert!(b.get(69));
+        assert!(!b.get(65));
+
+        // out of range doesn't change anything
+        assert_eq!(b.try_set(70, true), None);
+        assert_eq!(b.try_set(usize::MAX, true), None);
+        assert_eq!(b.count(), 1);
+    }
+
+    #[test]
+    fn check_swap() {
+        let mut b = BitSet::new(200);
+        b.set(3, true);
+        b.set(150, true);
+        let original = b.clone();
+
+        // in the same block
+        b.swap(3, 5);
+        assert!(!b.get(3));
+        assert!(b.get(5));
+        b.swap(3, 5);
+        assert_eq!(b, original);
+
+        // across the blocks
+        b.swap(3, 199);
+        assert!(!b.get(3));
+        assert!(b.get(199));
+        assert!(b.get(150));
+        b.swap(199, 3);
+        assert_eq!(b, original);
+
+        // the same values or the same position change nothing
+        b.swap(3, 150);
+        b.swap(3, 3);
+        b.swap(4, 4);
+        assert_eq!(b, original);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit position [70] is outside available range: [0, 69]")]
+    fn check_swap_outside_range() {
+        BitSet::new(70).swap(0, 70);
+    }
+
+    #[quickcheck]
+    fn check_set_all_range(value: u128, from: u8, to: u8) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let (from, to) = (from as usize % (size + 1), to as usize % (size + 1));
+            let (from, to) = (min(from, to), max(from, to));
+            for bit in &[true, false] {
+                let mut c = b.clone();
+                c.set_all_range(from, to, *bit);
+                for i in 0..*size {
+                    let expected = if (from..to).contains(&i) {
+                        *bit
+                    } else {
+                        b.get(i)
+                    };
+                    assert_eq!(c.get(i), expected);
+                }
+            }
+        }
+        true
+    }
+
+    #[test]
+    fn check_copy_bits_from_aligned() {
+        let src = make_bitset(200, 0xdead_beef_1234_5678_9abc_def0_1357_9bdf);
+        let mut dst = BitSet::new(200);
+        dst.copy_bits_from(&src, 0, 0, 200);
+        assert_eq!(dst, src);
+
+        let mut dst = BitSet::new(300);
+        dst.set_all(true);
+        dst.copy_bits_from(&src, 64, 128, 100);
+        for i in 0..300 {
+            let expected = if (128..228).contains(&i) {
+                src.get(i - 64)
+            } else {
+                true
+            };
+            assert_eq!(dst.get(i), expected);
+        }
+    }
+
+    #[quickcheck]
+    fn check_copy_bits_from_misaligned(value: u128, src_from: u8, dst_from: u8, len: u8) -> bool {
+        let src = make_bitset(300, value);
+        // both ranges must fit in the bitsets
+        let (src_from, dst_from, len) = (
+            src_from as usize % 150,
+            dst_from as usize % 150,
+            len as usize % 150,
+        );
+        let mut dst = make_bitset(300, !value);
+        let original = dst.clone();
+        dst.copy_bits_from(&src, src_from, dst_from, len);
+
+        for i in 0..300 {
+            let expected = if (dst_from..dst_from + len).contains(&i) {
+                src.get(i - dst_from + src_from)
+            } else {
+                original.get(i)
+            };
+            assert_eq!(dst.get(i), expected);
+        }
+        true
+    }
+
+    #[test]
+    fn check_copy_bits_from_with_zero_length() {
+        let src = make_bitset(70, u128::MAX);
+        let mut dst = BitSet::new(70);
+        dst.copy_bits_from(&src, 70, 70, 0);
+        assert!(dst.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit range [60, 80) is outside available range: [0, 69]")]
+    fn check_copy_bits_from_outside_source() {
+        BitSet::new(100).copy_bits_from(&BitSet::new(70), 60, 0, 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit range [60, 80) is outside available range: [0, 69]")]
+    fn check_copy_bits_from_outside_destination() {
+        BitSet::new(70).copy_bits_from(&BitSet::new(100), 0, 60, 20);
+    }
+
+    #[test]
+    fn check_extend_from_indices() {
+        let mut b = BitSet::new(200);
+        b.extend_from_indices((10..100).step_by(3));
+        assert_eq!(
+            b.iter_ones().collect::<Vec<_>>(),
+            (10..100).step_by(3).collect::<Vec<_>>()
+        );
+
+        // the bits already set are left set
+        b.extend([1, 4, 10, 199]);
+        assert!(b.get(1));
+        assert!(b.get(4));
+        assert!(b.get(10));
+        assert!(b.get(199));
+        assert_eq!(b.count(), 33);
+    }
+
+    #[quickcheck]
+    fn check_retain(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+
+            let mut c = b.clone();
+            c.retain(|i| i % 2 == 0);
+            for i in 0..*size {
+                assert_eq!(c.get(i), b.get(i) && i % 2 == 0);
+            }
+
+            let mut c = b.clone();
+            c.retain(|_| true);
+            assert_eq!(c, b);
+
+            // only the bits set to true are checked
+            let mut checked = vec![];
+            c.retain(|i| {
+                checked.push(i);
+                false
+            });
+            assert_eq!(checked, b.collect_ones());
+            assert!(c.is_empty());
+        }
+        true
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit position [70] is outside available range: [0, 69]")]
+    fn check_extend_from_indices_outside_range() {
+        BitSet::new(70).extend_from_indices([1, 70]);
+    }
+}
+
+#[cfg(test)]
+#[macro_use]
+mod test_utitily_functions {
+
+    use super::test_helpers::{make_bitset, SIZES};
+    use super::*;
+
+    #[test]
+    fn check_all_function() {
+        let mut b = BitSet::new(300);
+        assert!(!b.all());
+        b.set(10, true);
+        assert!(!b.all());
+
+        let mut b = BitSet::from(u128::MAX);
+        assert!(b.all());
+        b.set(10, false);
+        assert!(!b.all());
+    }
+
+    #[test]
+    fn check_all_function_for_partial_last_block() {
+        let mut b = BitSet::new(70);
+        b.set_all(true);
+        assert!(b.all());
+        assert!(b.any());
+        assert_eq!(b.count(), 70);
+
+        b.set(69, false);
+        assert!(!b.all());
+        assert!(b.any());
+
+        b.set_all(false);
+        assert!(!b.all());
+        assert!(!b.any());
+
+        let mut b = BitSet::new(1);
+        b.set(0, true);
+        assert!(b.all());
+    }
+
+    #[test]
+    fn check_any_function() {
+        let mut b = BitSet::new(300);
+        assert!(!b.any());
+        b.set(10, true);
+        assert!(b.any());
+
+        let mut b = BitSet::from(u128::MAX);
+        assert!(b.any());
+        b.set(10, false);
+        assert!(b.any());
+    }
+
+    #[test]
+    fn check_count_function() {
+        let mut b = BitSet::new(300);
+        assert_eq! {b.count(), 0}
+        b.set(10, true);
+        assert_eq! {b.count(), 1}
+
+        let mut b = BitSet::from(u128::MAX);
+        assert_eq! {b.count(), 128}
+        b.set(10, false);
+        assert_eq! {b.count(), 127}
+    }
+
+    #[quickcheck]
+    fn check_count_for_large_bitsets(value: u128, blocks: u8) -> bool {
+        let size = (blocks as usize + 1) * BitSet::block_size() - 5;
+        let b = make_bitset(size, value);
+        let mut expected = 0;
+        for block in &b.blocks {
+            expected += block.count_ones() as usize;
+        }
+        assert_eq!(b.count(), expected);
+        true
+    }
+
+    #[quickcheck]
+    fn check_parity_function(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            assert_eq!(b.parity(), b.count() % 2 == 1);
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_toggle_functions(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+
+            let mut c = b.clone();
+            c.toggle_all();
+            assert_eq!(c, b.complement());
+            c.toggle_all();
+            assert_eq!(c, b);
+
+            let mut d = b.clone();
+            d.toggle_range(0, *size);
+            assert_eq!(d, b.complement());
+
+            let mut e = b.clone();
+            e.flip_range(size / 3, size / 2);
+            d = b.clone();
+            d.toggle_range(size / 3, size / 2);
+            assert_eq!(d, e);
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_complement_function(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let c = b.complement();
+            assert_eq!(c.count(), size - b.count());
+            assert_eq!(c.complement(), b);
+            for i in 0..*size {
+                assert_eq!(c.get(i), !b.get(i));
+            }
+            // the bits outside the size are not set
+            assert_eq!(c.blocks[c.blocks.len() - 1] & !c.last_block_bitmask(), 0);
+        }
+        true
+    }
+
+    #[test]
+    fn check_parity_for_simple_bitsets() {
+        let mut b = BitSet::new(200);
+        assert!(!b.parity());
+        b.set(0, true);
+        assert!(b.parity());
+        b.set(199, true);
+        assert!(!b.parity());
+        b.set(64, true);
+        assert!(b.parity());
+    }
+
+    #[test]
+    fn check_single_bit_functions() {
+        for size in SIZES {
+            let mut b = BitSet::new(*size);
+            assert!(!b.is_single_bit());
+            assert_eq!(b.single_bit_index(), None);
+
+            for i in 0..*size {
+                b.set(i, true);
+                assert!(b.is_single_bit());
+                assert_eq!(b.single_bit_index(), Some(i));
+                b.set(i, false);
+            }
+
+            if *size > 1 {
+                b.set(0, true);
+                b.set(size - 1, true);
+                assert!(!b.is_single_bit());
+                assert_eq!(b.single_bit_index(), None);
+            }
+        }
+
+        // two bits in the same block
+        let mut b = BitSet::new(200);
+        b.set(65, true);
+        b.set(66, true);
+        assert_eq!(b.single_bit_index(), None);
+    }
+
+    #[quickcheck]
+    fn check_single_bit_for_random_values(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            assert_eq!(b.is_single_bit(), b.count() == 1);
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_count_zeros_function(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            assert_eq!(b.count() + b.count_zeros(), *size);
+            assert_eq!(b.count_zeros(), (0..*size).filter(|i| !b.get(*i)).count());
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_count_in_range_function(value: u128, from: u8, to: u8) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let (from, to) = (from as usize % (size + 1), to as usize % (size + 1));
+            let (from, to) = (min(from, to), max(from, to));
+            assert_eq!(
+                b.count_in_range(from, to),
+                (from..to).filter(|i| b.get(*i)).count()
+            );
+            assert_eq!(b.count_in_range(0, *size), b.count());
+        }
+        true
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit range [0, 71) is outside available range: [0, 69]")]
+    fn check_count_in_range_outside_size() {
+        BitSet::new(70).count_in_range(0, 71);
+    }
+
+    #[test]
+    fn check_count_zeros_ignores_unused_bits() {
+        let mut b = BitSet::new(70);
+        assert_eq!(b.count_zeros(), 70);
+
+        // the bits outside the size shouldn't be counted, even if set
+        b.blocks[1] = usize::MAX;
+        assert_eq!(b.count_zeros(), 64);
+    }
+
+    #[test]
+    fn check_count_leading_and_trailing_ones_functions() {
+        for size in SIZES {
+            let mut b = BitSet::new(*size);
+            assert_eq!(b.count_trailing_ones(), 0);
+            assert_eq!(b.count_leading_ones(), 0);
+
+            b.set_all(true);
+            assert_eq!(b.count_trailing_ones(), *size);
+            assert_eq!(b.count_leading_ones(), *size);
+
+            if *size == 1 {
+                continue;
+            }
+            // the lower half set
+            b.clear();
+            b.flip_range(0, size / 2);
+            assert_eq!(b.count_trailing_ones(), size / 2);
+            assert_eq!(b.count_leading_ones(), 0);
+
+            // the upper half set
+            b.negate();
+            assert_eq!(b.count_trailing_ones(), 0);
+            assert_eq!(b.count_leading_ones(), size - size / 2);
+        }
+        assert_eq!(BitSet::new_filled(70, true).count_leading_ones(), 70);
+    }
+
+    #[quickcheck]
+    fn check_count_leading_and_trailing_ones_for_random_values(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let trailing = (0..*size).take_while(|i| b.get(*i)).count();
+            let leading = (0..*size).rev().take_while(|i| b.get(*i)).count();
+            assert_eq!(b.count_trailing_ones(), trailing);
+            assert_eq!(b.count_leading_ones(), leading);
+        }
+        true
+    }
+
+    #[test]
+    fn check_next_and_prev_set_bit_functions() {
+        let mut b = BitSet::new(200);
+        assert_eq!(b.next_set_bit(0), None);
+        assert_eq!(b.prev_set_bit(199), None);
+
+        b.set(3, true);
+        b.set(64, true);
+        b.set(199, true);
+        assert_eq!(b.next_set_bit(0), Some(3));
+        assert_eq!(b.next_set_bit(3), Some(3));
+        assert_eq!(b.next_set_bit(4), Some(64));
+        assert_eq!(b.next_set_bit(65), Some(199));
+        assert_eq!(b.next_set_bit(199), Some(199));
+        assert_eq!(b.prev_set_bit(199), Some(199));
+        assert_eq!(b.prev_set_bit(198), Some(64));
+        assert_eq!(b.prev_set_bit(63), Some(3));
+        assert_eq!(b.prev_set_bit(2), None);
+
+        // the positions outside the size
+        assert_eq!(b.next_set_bit(200), None);
+        assert_eq!(b.next_set_bit(usize::MAX), None);
+        assert_eq!(b.prev_set_bit(200), Some(199));
+        assert_eq!(b.prev_set_bit(usize::MAX), Some(199));
+    }
+
+    #[quickcheck]
+    fn check_next_and_prev_set_bit_for_random_values(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            for from in 0..=*size {
+                assert_eq!(b.next_set_bit(from), (from..*size).find(|i| b.get(*i)));
+                let to = min(from + 1, *size);
+                assert_eq!(b.prev_set_bit(from), (0..to).rev().find(|i| b.get(*i)));
+            }
+        }
+        true
+    }
+
+    #[test]
+    fn check_min_and_max_functions() {
+        for size in SIZES {
+            let mut b = BitSet::new(*size);
+            assert_eq!(b.min(), None);
+            assert_eq!(b.max(), None);
+
+            for i in 0..*size {
+                b.set(i, true);
+                assert_eq!(b.min(), Some(i));
+                assert_eq!(b.max(), Some(i));
+                b.set(i, false);
+            }
+
+            b.set(0, true);
+            b.set(size - 1, true);
+            assert_eq!(b.min(), Some(0));
+            assert_eq!(b.max(), Some(size - 1));
+        }
+    }
+
+    #[quickcheck]
+    fn check_find_first_and_last_set_for_random_values(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            assert_eq!(b.find_first_set(), (0..*size).find(|i| b.get(*i)));
+            assert_eq!(b.find_last_set(), (0..*size).rev().find(|i| b.get(*i)));
+            assert_eq!(b.min(), b.find_first_set());
+            assert_eq!(b.max(), b.find_last_set());
+        }
+        true
+    }
+
+    #[test]
+    fn check_first_and_last_zero_functions() {
+        for size in SIZES {
+            let mut b = BitSet::new(*size);
+            assert_eq!(b.first_zero(), Some(0));
+            assert_eq!(b.last_zero(), Some(size - 1));
+
+            b.set_all(true);
+            assert_eq!(b.first_zero(), None);
+            assert_eq!(b.last_zero(), None);
+
+            b.set(size - 1, false);
+            assert_eq!(b.first_zero(), Some(size - 1));
+            assert_eq!(b.last_zero(), Some(size - 1));
+
+            b.set(0, false);
+            assert_eq!(b.first_zero(), Some(0));
+            assert_eq!(b.last_zero(), Some(size - 1));
+        }
+    }
+
+    #[quickcheck]
+    fn check_first_and_last_zero_for_random_values(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            assert_eq!(b.first_zero(), (0..*size).find(|i| !b.get(*i)));
+            assert_eq!(b.last_zero(), (0..*size).rev().find(|i| !b.get(*i)));
+        }
+        true
+    }
+
+    #[test]
+    fn check_trailing_and_leading_zeros_functions() {
+        for size in SIZES {
+            let mut b = BitSet::new(*size);
+            assert_eq!(b.trailing_zeros(), *size);
+            assert_eq!(b.leading_zeros(), *size);
+
+            b.set(0, true);
+            assert_eq!(b.trailing_zeros(), 0);
+            assert_eq!(b.leading_zeros(), size - 1);
+
+            b.set(0, false);
+            b.set(size - 1, true);
+            assert_eq!(b.trailing_zeros(), size - 1);
+            assert_eq!(b.leading_zeros(), 0);
+
+            b.negate();
+            b.set(size - 1, true);
+            assert_eq!(b.trailing_zeros(), 0);
+            assert_eq!(b.leading_zeros(), 0);
+        }
+    }
+
+    #[quickcheck]
+    fn check_trailing_and_leading_zeros_for_random_values(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let trailing = (0..*size).take_while(|i| !b.get(*i)).count();
+            let leading = (0..*size).rev().take_while(|i| !b.get(*i)).count();
+            assert_eq!(b.trailing_zeros(), trailing);
+            assert_eq!(b.leading_zeros(), leading);
+        }
+        true
+    }
+
+    #[test]
+    fn check_clear_function() {
+        let mut b = BitSet::new(200);
+        assert!(b.is_empty());
+        b.set(0, true);
+        b.set(100, true);
+        b.set(199, true);
+        assert!(!b.is_empty());
+
+        b.clear();
+        assert_eq!(b.count(), 0);
+        assert!(b.is_empty());
+        assert_eq!(b.len(), 200);
+    }
+
+    #[test]
+    fn check_memory_usage_function() {
+        let expected = |b: &BitSet| b.blocks.capacity() * size_of::<usize>() + size_of::<BitSet>();
+
+        let mut b = BitSet::new(70);
+        assert_eq!(b.memory_usage(), expected(&b));
+        assert!(b.memory_usage() >= 2 * size_of::<usize>() + size_of::<BitSet>());
+
+        b.resize(1000);
+        assert_eq!(b.memory_usage(), expected(&b));
+        assert!(b.memory_usage() >= BitSet::blocks_number(1000) * size_of::<usize>());
+
+        // shrinking doesn't release the memory
+        let before = b.memory_usage();
+        b.resize(10);
+        assert_eq!(b.memory_usage(), expected(&b));
+        assert_eq!(b.memory_usage(), before);
+    }
+
+    #[test]
+    fn check_mix_hash_function() {
+        let mut hashes = vec![BitSet::new(70).mix_hash()];
+        for i in 0..70 {
+            let mut b = BitSet::new(70);
+            b.set(i, true);
+            assert_eq!(b.mix_hash(), b.clone().mix_hash());
+            hashes.push(b.mix_hash());
+        }
+        // the sizes matter as well
+        for size in 1..70 {
+            hashes.push(BitSet::new(size).mix_hash());
+        }
+
+        let count = hashes.len();
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert_eq!(hashes.len(), count);
+    }
+
+    #[test]
+    fn check_count_transitions_function() {
+        // constant
+        assert_eq!(BitSet::new(200).count_transitions(), 0);
+        let mut b = BitSet::new(200);
+        b.negate();
+        assert_eq!(b.count_transitions(), 0);
+        assert_eq!(BitSet::new(1).count_transitions(), 0);
+
+        // alternating
+        for size in &[2, 7, 63, 64, 65, 70, 128, 200] {
+            let b = BitSet::checkerboard(*size, true);
+            assert_eq!(b.count_transitions(), size - 1);
+        }
+
+        // crossing the blocks
+        let mut b = BitSet::new(200);
+        b.flip_range(60, 130);
+        assert_eq!(b.count_transitions(), 2);
+        b.flip(64);
+        assert_eq!(b.count_transitions(), 4);
+        b.flip(199);
+        assert_eq!(b.count_transitions(), 5);
+    }
+
+    #[quickcheck]
+    fn check_count_transitions_for_random_values(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let expected = (1..*size).filter(|i| b.get(*i) != b.get(i - 1)).count();
+            assert_eq!(b.count_transitions(), expected);
+        }
+        true
+    }
+
+    #[test]
+    fn check_density_function() {
+        let mut b = BitSet::new(70);
+        assert_eq!(b.density(), 0.0);
+
+        for i in 0..35 {
+            b.set(i * 2, true);
+        }
+        assert_eq!(b.density(), 0.5);
+
+        let full = BitSet::from(u128::MAX);
+        assert_eq!(full.density(), 1.0);
+
+        let mut b = BitSet::new(70);
+        b.negate();
+        assert_eq!(b.density(), 1.0);
+
+        let mut b = BitSet::new(100);
+        b.flip_range(50, 100);
+        assert_eq!(b.density(), 0.5);
+        assert_eq!(BitSet::checkerboard(100, true).density(), 0.5);
+    }
+
+    #[quickcheck]
+    fn check_density_for_random_values(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let density = b.density();
+            assert!((0.0..=1.0).contains(&density));
+            assert_eq!(density, b.count() as f64 / *size as f64);
+        }
+        true
+    }
+
+    #[test]
+    fn check_clone_into_reuses_allocation() {
+        let mut src = BitSet::new(70);
+        src.set(1, true);
+        src.set(69, true);
+
+        let mut dst = BitSet::new(1000);
+        dst.set(999, true);
+        let capacity = dst.blocks.capacity();
+        let pointer = dst.blocks.as_ptr();
+
+        src.clone_into(&mut dst);
+        assert_eq!(dst, src);
+        assert_eq!(dst.blocks.capacity(), capacity);
+        assert_eq!(dst.blocks.as_ptr(), pointer);
+
+        // too small destination is enlarged
+        let mut small = BitSet::new(1);
+        dst.clone_into(&mut small);
+        assert_eq!(small, dst);
+    }
+
+    /// Checks conversion from different values
+    macro_rules! check_enlarge_function {
+        ($func:ident, $t:ty) => {
+            #[quickcheck]
+            fn $func(value: $t) -> bool {
+                let initial_size = size_of::<$t>() * 8;
+                let new_size = value as usize;
+
+                let mut b = BitSet::from(value);
+                assert_eq!(b.size, initial_size);
+
+                let res: Result<(), BitSetError> = b.enlarge(new_size as usize);
+
+                if new_size <= initial_size {
+                    let expected = BitSetError::EnlargeError {
+                        from: initial_size,
+                        to: new_size,
+                    };
+                    let error = res.err().unwrap();
+                    assert_eq!(expected, error);
+                } else {
+                    assert!(res.is_ok());
+                    assert_eq!(b.size, new_size);
+                    assert_eq!(b.blocks.len(), BitSet::blocks_number(new_size));
+                    assert_eq!(<$t>::try_from(b).unwrap(), value);
+                }
+                true
+            }
+        };
+    }
+
+    check_enlarge_function! {check_enlarge_function_for_u8, u8}
+    check_enlarge_function! {check_enlarge_function_for_u16, u16}
+    check_enlarge_function! {check_enlarge_function_for_u32, u32}
+    check_enlarge_function! {check_enlarge_function_for_u64, u64}
+    check_enlarge_function! {check_enlarge_function_for_u128, u128}
+    check_enlarge_function! {check_enlarge_function_for_usize, usize}
+}
+
+#[cfg(test)]
+mod test_flip_functions {
+    use super::test_helpers::{make_bitset, SIZES};
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Bit position [70] is outside available range: [0, 69]")]
+    fn check_flipping_too_large_position() {
+        let mut b = BitSet::new(70);
+        b.flip(70);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit range [0, 71) is outside available range: [0, 69]")]
+    fn check_flipping_too_large_range() {
+        let mut b = BitSet::new(70);
+        b.flip_range(0, 71);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit range [5, 4) is outside available range: [0, 69]")]
+    fn check_flipping_reversed_range() {
+        let mut b = BitSet::new(70);
+        b.flip_range(5, 4);
+    }
+
+    #[test]
+    fn check_flip() {
+        let mut b = BitSet::new(70);
+        b.flip(0);
+        b.flip(65);
+        assert!(b.get(0));
+        assert!(b.get(65));
+        assert_eq!(b.count(), 2);
+
+        b.flip(65);
+        assert!(!b.get(65));
+        assert_eq!(b.count(), 1);
+    }
+
+    #[quickcheck]
+    fn check_flipping_twice_is_noop(value: u128, position: u8) -> bool {
+        let position = position as usize;
+        let b = make_bitset(200, value);
+
+        let mut c = b.clone();
+        c.flip(position);
+        assert_eq!(c.get(position), !b.get(position));
+        c.flip(position);
+        c == b
+    }
+
+    #[quickcheck]
+    fn check_flip_range(value: u128, from: u8, to: u8) -> bool {
+        let (from, to) = (min(from, to) as usize, max(from, to) as usize);
+        let b = make_bitset(200, value);
+
+        let mut c = b.clone();
+        c.flip_range(from, to);
+        for i in 0..200 {
+            assert_eq!(c.get(i), b.get(i) != (from..to).contains(&i));
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_flip_range_for_whole_set_equals_negate(value: u128) -> bool {
+        for size in &[1, 63, 64, 65, 70, 128, 200] {
+            let mut a = make_bitset(*size, value);
+            let mut b = a.clone();
+            a.flip_range(0, *size);
+            b.negate();
+            assert_eq!(a, b);
+        }
+        true
+    }
+
+    #[test]
+    fn check_count_runs_function() {
+        let mut b = BitSet::new(200);
+        assert_eq!(b.count_runs(true), 0);
+        assert_eq!(b.count_runs(false), 1);
+
+        // alternating bits
+        for i in 0..100 {
+            b.set(2 * i, true);
+        }
+        assert_eq!(b.count_runs(true), 100);
+        assert_eq!(b.count_runs(false), 100);
+
+        // a single run of ones crossing the blocks
+        let mut b = BitSet::new(200);
+        b.flip_range(10, 150);
+        assert_eq!(b.count_runs(true), 1);
+        assert_eq!(b.count_runs(false), 2);
+
+        let mut b = BitSet::new(70);
+        b.negate();
+        assert_eq!(b.count_runs(true), 1);
+        assert_eq!(b.count_runs(false), 0);
+    }
+
+    #[test]
+    fn check_longest_run_functions() {
+        for size in SIZES {
+            let mut b = BitSet::new(*size);
+            assert_eq!(b.longest_run_ones(), 0);
+            assert_eq!(b.longest_run_zeros(), *size);
+
+            b.set_all(true);
+            assert_eq!(b.longest_run_ones(), *size);
+            assert_eq!(b.longest_run_zeros(), 0);
+        }
+
+        let mut b = BitSet::new(200);
+        b.flip_range(3, 5);
+        b.flip_range(60, 130);
+        b.flip_range(190, 200);
+        assert_eq!(b.longest_run_ones(), 70);
+        assert_eq!(b.longest_run_zeros(), 60);
+    }
+
+    #[quickcheck]
+    fn check_longest_run_for_random_values(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            for bit in &[true, false] {
+                let mut expected = 0;
+                let mut length = 0;
+                for i in 0..*size {
+                    length = if b.get(i) == *bit { length + 1 } else { 0 };
+                    expected = max(expected, length);
+                }
+                let res = if *bit {
+                    b.longest_run_ones()
+                } else {
+                    b.longest_run_zeros()
+                };
+                assert_eq!(res, expected);
+            }
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_count_runs(value: u128) -> bool {
+        for size in &[1, 63, 64, 65, 70, 128, 200] {
+            let b = make_bitset(*size, value);
+            for bit in &[true, false] {
+                let expected = (0..*size)
+                    .filter(|i| b.get(*i) == *bit && (*i == 0 || b.get(i - 1) != *bit))
+                    .count();
+                assert_eq!(b.count_runs(*bit), expected);
+            }
+        }
+        true
+    }
+
+    #[test]
+    #[should_panic(expected = "BitSets have different sizes: 70 and 71.")]
+    fn check_toggle_masked_with_different_size() {
+        let mut b = BitSet::new(70);
+        b.toggle_masked(&BitSet::new(71));
+    }
+
+    #[quickcheck]
+    fn check_toggle_masked(value: u128, mask: u128) -> bool {
+        for size in &[1, 63, 64, 65, 70, 128, 200] {
+            let b = make_bitset(*size, value);
+            let m = make_bitset(*size, mask);
+
+            let mut c = b.clone();
+            c.toggle_masked(&m);
+            for i in 0..*size {
+                // only the masked bits are flipped
+                assert_eq!(c.get(i), b.get(i) != m.get(i));
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test_resizing_functions {
+    use super::test_helpers::{make_bitset, SIZES};
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Resizing BitSet to zero bits is not allowed.")]
+    fn check_resizing_with_to_zero_bits() {
+        let mut b = BitSet::new(10);
+        b.resize_with(0, |_| true);
+    }
+
+    #[test]
+    fn check_growing_with_alternating_bits() {
+        let mut b = BitSet::new(70);
+        b.set(0, true);
+        b.set(69, true);
+
+        let mut positions = vec![];
+        b.resize_with(200, |i| {
+            positions.push(i);
+            i % 2 == 0
+        });
+
+        // the closure gets the absolute positions of the new bits
+        assert_eq!(positions, (70..200).collect::<Vec<usize>>());
+
+        assert_eq!(b.size, 200);
+        assert_eq!(b.blocks.len(), BitSet::blocks_number(200));
+        // the old bits are not changed
+        assert!(b.get(0));
+        assert!(!b.get(1));
+        assert!(b.get(69));
+        for i in 70..200 {
+            assert_eq!(b.get(i), i % 2 == 0);
+        }
+        assert_eq!(b.count(), 2 + 65);
+    }
+
+    #[test]
+    #[should_panic(expected = "Resizing BitSet to zero bits is not allowed.")]
+    fn check_resizing_to_zero_bits() {
+        BitSet::new(10).resize(0);
+    }
+
+    #[test]
+    fn check_growing_across_block_boundary() {
+        let block_size = BitSet::block_size();
+        let mut b = BitSet::new(block_size - 1);
+        b.set(0, true);
+        b.set(block_size - 2, true);
+
+        b.resize(block_size + 1);
+        assert_eq!(b.len(), block_size + 1);
+        assert_eq!(b.blocks.len(), 2);
+        assert!(b.get(0));
+        assert!(b.get(block_size - 2));
+        assert!(!b.get(block_size - 1));
+        assert!(!b.get(block_size));
+        assert_eq!(b.count(), 2);
+    }
+
+    #[test]
+    fn check_shrinking_hides_high_bits() {
+        let block_size = BitSet::block_size();
+        let mut b = BitSet::new(3 * block_size);
+        b.set(1, true);
+        b.set(10, true);
+        b.set(block_size + 5, true);
+        b.set(3 * block_size - 1, true);
+
+        b.resize(10);
+        assert_eq!(b.len(), 10);
+        assert_eq!(b.blocks.len(), 1);
+        assert_eq!(b.count(), 1);
+        assert_eq!(b.try_get(10), None);
+
+        // the bits don't appear again after growing
+        b.resize(3 * block_size);
+        assert_eq!(b.count(), 1);
+        assert!(b.get(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Truncating BitSet to zero bits is not allowed.")]
+    fn check_truncating_to_zero_bits() {
+        BitSet::new(10).truncate(0);
+    }
+
+    #[quickcheck]
+    fn check_truncating(value: u128, to_size: u8) -> bool {
+        let to_size = to_size as usize + 1;
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let mut c = b.clone();
+            c.truncate(to_size);
+
+            if to_size >= *size {
+                assert_eq!(c, b);
+                continue;
+            }
+            assert_eq!(c.len(), to_size);
+            assert_eq!(c.blocks.len(), BitSet::blocks_number(to_size));
+            assert_eq!(c.count(), b.rank(to_size));
+            for i in 0..to_size {
+                assert_eq!(c.get(i), b.get(i));
+            }
+            assert_eq!(c.try_get(to_size), None);
+        }
+        true
+    }
+
+    #[test]
+    fn check_appending_small_bitset() {
+        let mut a = BitSet::new(70);
+        a.set(0, true);
+        a.set(69, true);
+
+        let mut b = BitSet::new(3);
+        b.set(0, true);
+        b.set(2, true);
+
+        a.append(&b);
+        assert_eq!(a.len(), 73);
+        assert_eq!(a.blocks.len(), BitSet::blocks_number(73));
+        assert_eq!(a.count(), 4);
+        assert!(a.get(0));
+        assert!(a.get(69));
+        assert!(a.get(70));
+        assert!(!a.get(71));
+        assert!(a.get(72));
+    }
+
+    #[quickcheck]
+    fn check_appending(left: u128, right: u128) -> bool {
+        for left_size in &[1, 7, 63, 64, 65, 70, 128] {
+            for right_size in SIZES {
+                let a = make_bitset(*left_size, left);
+                let b = make_bitset(*right_size, right);
+                let mut c = a.clone();
+                c.append(&b);
+
+                assert_eq!(c.len(), left_size + right_size);
+                assert_eq!(c.count(), a.count() + b.count());
+                for i in 0..*left_size {
+                    assert_eq!(c.get(i), a.get(i));
+                }
+                for j in 0..*right_size {
+                    assert_eq!(c.get(left_size + j), b.get(j));
+                }
+            }
+        }
+        true
+    }
+
+    #[test]
+    fn check_shrinking_to_fit() {
+        let block_size = BitSet::block_size();
+        let mut b = BitSet::new(10);
+        b.set(3, true);
+        b.resize(10 * block_size);
+        b.truncate(block_size + 1);
+        assert!(b.capacity() >= 10 * block_size);
+
+        let before = b.clone();
+        b.shrink_to_fit();
+        assert_eq!(b.capacity(), 2 * block_size);
+        assert_eq!(b, before);
+    }
+
+    #[quickcheck]
+    fn check_growing_and_shrinking_by(value: u128, by: u8) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let by = by as usize;
+
+            let mut c = b.clone();
+            c.grow(by);
+            assert_eq!(c.size, size + by);
+            assert_eq!(c.blocks.len(), BitSet::blocks_number(size + by));
+            assert_eq!(c.count_in_range(*size, size + by), 0);
+
+            c.shrink(by);
+            assert_eq!(c, b);
+        }
+        true
+    }
+
+    #[test]
+    fn check_shrinking_by_drops_highest_bits() {
+        let mut b = BitSet::new(70);
+        b.set_all(true);
+        b.shrink(7);
+        assert_eq!(b.size, 63);
+        assert_eq!(b.blocks.len(), BitSet::blocks_number(63));
+        assert_eq!(b.count(), 63);
+
+        b.grow(7);
+        assert_eq!(b.count(), 63);
+        assert!(!b.get(69));
+    }
+
+    #[test]
+    #[should_panic(expected = "Shrinking BitSet to zero bits is not allowed.")]
+    fn check_shrinking_by_whole_size() {
+        BitSet::new(70).shrink(70);
+    }
+
+    #[test]
+    #[should_panic(expected = "Growing BitSet above usize::MAX bits is not allowed.")]
+    fn check_growing_above_max_size() {
+        BitSet::new(70).grow(usize::MAX);
+    }
+
+    #[test]
+    fn check_reserving() {
+        let block_size = BitSet::block_size();
+        let mut b = BitSet::new(10);
+        b.set(3, true);
+        b.set(9, true);
+        let before = b.clone();
+
+        b.reserve(10 * block_size);
+        assert!(b.capacity() >= 10 * block_size + 10);
+        assert_eq!(b.len(), 10);
+        assert_eq!(b.count(), 2);
+        assert_eq!(b, before);
+
+        // growing up to the reserved size doesn't reallocate
+        let pointer = b.blocks.as_ptr();
+        for _ in 0..10 * block_size {
+            b.push(true);
+        }
+        assert_eq!(b.blocks.as_ptr(), pointer);
+
+        // nothing to reserve for the bits already in the last block
+        let mut b = BitSet::new(1);
+        b.reserve(block_size - 1);
+        assert_eq!(b.capacity(), block_size);
+    }
+
+    #[test]
+    #[should_panic(expected = "Reserving BitSet above usize::MAX bits is not allowed.")]
+    fn check_reserving_above_max_size() {
+        BitSet::new(70).reserve(usize::MAX);
+    }
+
+    #[quickcheck]
+    fn check_pushing_and_popping(bits: Vec<bool>) -> bool {
+        let mut b = BitSet::new(1);
+        b.set(0, true);
+        for bit in &bits {
+            b.push(*bit);
+        }
+        assert_eq!(b.len(), bits.len() + 1);
+        assert_eq!(b.blocks.len(), BitSet::blocks_number(bits.len() + 1));
+        for (i, bit) in bits.iter().enumerate() {
+            assert_eq!(b.get(i + 1), *bit);
+        }
+
+        for bit in bits.iter().rev() {
+            assert_eq!(b.pop(), Some(*bit));
+        }
+        assert_eq!(b.len(), 1);
+        assert_eq!(b.blocks.len(), 1);
+
+        // the last bit cannot be removed
+        assert_eq!(b.pop(), None);
+        assert_eq!(b.len(), 1);
+        assert!(b.get(0));
+        true
+    }
+
+    #[test]
+    fn check_growing_to_block_count() {
+        let block_size = BitSet::block_size();
+        let mut b = BitSet::new(70);
+        b.set(69, true);
+
+        b.grow_to_block_count(4);
+        assert_eq!(b.size, 4 * block_size);
+        assert_eq!(b.blocks.len(), 4);
+        assert!(b.get(69));
+        assert_eq!(b.count(), 1);
+
+        // it never shrinks
+        b.grow_to_block_count(1);
+        assert_eq!(b.size, 4 * block_size);
+        assert_eq!(b.blocks.len(), 4);
+
+        // the last block is filled up
+        let mut b = BitSet::new(block_size + 6);
+        b.grow_to_block_count(2);
+        assert_eq!(b.size, 2 * block_size);
+        assert_eq!(b.blocks.len(), 2);
+    }
+
+    #[test]
+    fn check_shrinking_with() {
+        let mut b = BitSet::new(200);
+        b.set(3, true);
+        b.set(65, true);
+        b.set(150, true);
+
+        b.resize_with(66, |_| panic!("Shrinking shouldn't add any bits."));
+        assert_eq!(b.size, 66);
+        assert_eq!(b.blocks.len(), BitSet::blocks_number(66));
+        assert!(b.get(3));
+        assert!(b.get(65));
+        assert_eq!(b.count(), 2);
+
+        b.resize_with(65, |_| true);
+        assert_eq!(b.count(), 1);
+
+        // the dropped bits are not visible after growing again
+        b.resize_with(200, |_| false);
+        assert_eq!(b.count(), 1);
+    }
+
+    #[quickcheck]
+    fn check_split_at(value: u128) -> bool {
+        for size in &[7, 63, 64, 65, 70, 128, 200] {
+            let b = make_bitset(*size, value);
+            for position in 1..*size {
+                let (low, high) = b.split_at(position);
+                assert_eq!(low.size, position);
+                assert_eq!(high.size, size - position);
+                assert_eq!(high.blocks.len(), BitSet::blocks_number(size - position));
+
+                let mut joined = low.clone();
+                joined.append(&high);
+                assert_eq!(joined, b);
+            }
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_concat(left: u128, right: u128) -> bool {
+        for left_size in &[1, 7, 63, 64, 65, 70, 128] {
+            for right_size in &[1, 7, 63, 64, 65, 200] {
+                let a = make_bitset(*left_size, left);
+                let b = make_bitset(*right_size, right);
+                let c = a.concat(&b);
+                assert_eq!(c.size, left_size + right_size);
+                for i in 0..*left_size {
+                    assert_eq!(c.get(i), a.get(i));
+                }
+                for i in 0..*right_size {
+                    assert_eq!(c.get(left_size + i), b.get(i));
+                }
+                assert_eq!(c.split_at(*left_size), (a, b));
+            }
+        }
+        true
+    }
+
+    #[test]
+    #[should_panic(expected = "Split position [0] is outside available range: [1, 69]")]
+    fn check_split_at_zero() {
+        BitSet::new(70).split_at(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Split position [70] is outside available range: [1, 69]")]
+    fn check_split_at_size() {
+        BitSet::new(70).split_at(70);
+    }
+
+    #[test]
+    fn check_set_grow() {
+        let mut b = BitSet::new(10);
+        b.set(9, true);
+
+        b.set_grow(500, true);
+        assert_eq!(b.size, 501);
+        assert_eq!(b.blocks.len(), BitSet::blocks_number(501));
+        assert!(b.get(9));
+        assert!(b.get(500));
+        // the new bits between are false
+        assert_eq!(b.count_in_range(10, 500), 0);
+
+        // setting inside the size doesn't shrink
+        b.set_grow(3, true);
+        b.set_grow(500, false);
+        assert_eq!(b.size, 501);
+        assert_eq!(b.collect_ones(), vec![3, 9]);
+
+        // growing by setting to false still grows
+        b.set_grow(600, false);
+        assert_eq!(b.size, 601);
+        assert_eq!(b.count(), 2);
+    }
+}
+
+/// Implements the operator for all the combinations of owned and borrowed bitsets.
+///
+/// The smaller bitset is treated as if it had the missing higher bits set to false,
+/// and the result has the size of the larger one.
+/// When the left bitset is owned, its blocks are reused for the result.
+macro_rules! impl_bitset_operator {
+    ($trait:ident, $func:ident, $op:expr) => {
+        impl $trait<&BitSet> for &BitSet {
+            type Output = BitSet;
+
+            fn $func(self, rhs: &BitSet) -> Self::Output {
+                self.combine_extended(rhs, $op)
+            }
+        }
+
+        impl $trait<BitSet> for BitSet {
+            type Output = BitSet;
+
+            fn $func(mut self, rhs: BitSet) -> Self::Output {
+                self.combine_extend(&rhs, $op);
+                self
+            }
+        }
+
+        impl $trait<&BitSet> for BitSet {
+            type Output = BitSet;
+
+            fn $func(mut self, rhs: &BitSet) -> Self::Output {
+                self.combine_extend(rhs, $op);
+                self
+            }
+        }
+
+        impl $trait<BitSet> for &BitSet {
+            type Output = BitSet;
+
+            fn $func(self, rhs: BitSet) -> Self::Output {
+                self.combine_extended(&rhs, $op)
+            }
+        }
+    };
+}
+
+impl_bitset_operator!(BitAnd, bitand, |l, r| l & r);
+impl_bitset_operator!(BitOr, bitor, |l, r| l | r);
+impl_bitset_operator!(BitXor, bitxor, |l, r| l ^ r);
+impl_bitset_operator!(Sub, sub, |l: usize, r: usize| l & !r);
+
+impl BitAndAssign for BitSet {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.combine_extend(&rhs, |l, r| l & r);
+    }
+}
+
+/// Shifts the bits towards the higher positions, keeping the size.
+/// The bits shifted above the size are dropped.
+impl ShlAssign<usize> for BitSet {
+    fn shl_assign(&mut self, rhs: usize) {
+        if rhs >= self.size {
+            self.clear();
+            return;
+        }
+
+        let block_size = Self::block_size();
+        let blocks_shift = rhs / block_size;
+        let bits_shift = rhs % block_size;
+
+        // going from the top, so the source blocks are not overwritten before reading
+        for i in (0..self.blocks.len()).rev() {
+            let mut block = 0;
+            if i >= blocks_shift {
+                let source = i - blocks_shift;
+                block = self.blocks[source] << bits_shift;
+                if bits_shift != 0 && source > 0 {
+                    block |= self.blocks[source - 1] >> (block_size - bits_shift);
+                }
+            }
+            self.blocks[i] = block;
         }
-        res = res[(res.len() - self.size)..].to_string();
-        write!(f, "{}", res)
+        self.clear_unused_bits();
     }
 }
 
-macro_rules! add_try_from_uint_trait {
-    ($t:ty) => {
-        impl TryFrom<BitSet> for $t {
-            type Error = &'static str;
+impl Shl<usize> for BitSet {
+    type Output = BitSet;
 
-            fn try_from(value: BitSet) -> Result<Self, Self::Error> {
-                let block_size = size_of::<usize>();
-                let output_bytes = size_of::<$t>();
-                let blocks_needed: usize = max(output_bytes / block_size, 1);
-
-                // When the type is smaller than usize
-                if blocks_needed == 1 {
-                    match <$t>::try_from(value.blocks[0]) {
-                        Ok(number) => return Ok(number),
-                        Err(_) => return Err("Value stored in BitSet cannot be converted to u8."),
-                    };
-                }
+    fn shl(mut self, rhs: usize) -> Self::Output {
+        self <<= rhs;
+        self
+    }
+}
 
-                // The type is bigger than usize, e.g. u128 on 64bit machine,
-                // this way we need to have at least two blocks, and conversion
-                // should be fine.
+impl Shl<usize> for &BitSet {
+    type Output = BitSet;
 
-                // if any of the not used blocks contains any bit set, then the conversion is not doable
-                for block in &value.blocks[blocks_needed - 1..] {
-                    if *block != 0 {
-                        return Err("Value stored in BitSet cannot be converted to u8.");
-                    }
-                }
+    fn shl(self, rhs: usize) -> Self::Output {
+        let mut output = self.clone();
+        output <<= rhs;
+        output
+    }
+}
 
-                let mut output: $t = 0;
-                for block in &value.blocks[0..blocks_needed - 1] {
-                    output = output << BitSet::block_size();
-                    output = output | (*block) as $t;
-                }
-                return Ok(output);
-            }
+/// Shifts the bits towards the lower positions, keeping the size.
+/// The bits shifted below zero are dropped.
+impl ShrAssign<usize> for BitSet {
+    fn shr_assign(&mut self, rhs: usize) {
+        if rhs >= self.size {
+            self.clear();
+            return;
         }
-    };
+        self.shift_blocks_right(rhs);
+    }
 }
 
-add_try_from_uint_trait! {u8}
-add_try_from_uint_trait! {u16}
-add_try_from_uint_trait! {u32}
-add_try_from_uint_trait! {u64}
-add_try_from_uint_trait! {u128}
-add_try_from_uint_trait! {usize}
+impl Shr<usize> for BitSet {
+    type Output = BitSet;
 
-#[cfg(test)]
-mod test_private_functions {
+    fn shr(mut self, rhs: usize) -> Self::Output {
+        self >>= rhs;
+        self
+    }
+}
+
+impl Shr<usize> for &BitSet {
+    type Output = BitSet;
+
+    fn shr(self, rhs: usize) -> Self::Output {
+        let mut output = self.clone();
+        output >>= rhs;
+        output
+    }
+}
+
+static TRUE: bool = true;
+static FALSE: bool = false;
+
+/// Allows reading a bit with `bitset[i]`.
+///
+/// There is no IndexMut, as the bits are not stored as bools,
+/// so there is no `&mut bool` to return. Use `set()` instead.
+///
+/// Panics:
+///    - if the index is outside the bitset size
+///
+impl Index<usize> for BitSet {
+    type Output = bool;
+
+    fn index(&self, i: usize) -> &Self::Output {
+        if self.get(i) {
+            &TRUE
+        } else {
+            &FALSE
+        }
+    }
+}
+
+/// Sets to true the bits at the positions, the same as `extend_from_indices()`.
+///
+/// Panics:
+///    - if any position is larger than the max bit number (which is size-1)
+///
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        self.extend_from_indices(iter);
+    }
+}
 
+#[cfg(test)]
+#[macro_use]
+mod test_operators {
     use super::*;
 
+    #[quickcheck]
+    fn check_index(value: u128) -> bool {
+        let b = super::test_helpers::make_bitset(200, value);
+        for i in 0..200 {
+            assert_eq!(b[i], b.get(i));
+        }
+        true
+    }
+
     #[test]
-    fn check_getting_number_of_blocks() {
-        let block_size = size_of::<usize>() * 8;
+    #[should_panic(expected = "Bit position [70] is outside available range: [0, 69]")]
+    fn check_index_outside_range() {
+        let _ = BitSet::new(70)[70];
+    }
 
-        assert_eq!(BitSet::blocks_number(1), 1);
-        assert_eq!(BitSet::blocks_number(10), 1);
+    /// Checks the operator for all the combinations of owned and borrowed operands
+    /// against the operation on each bit.
+    macro_rules! check_bitset_operator {
+        ($func:ident, $trait:ident, $method:ident, $bit_operation:expr) => {
+            #[quickcheck]
+            fn $func(left: u128, right: u128) -> bool {
+                for (left_size, right_size) in &[(70, 70), (64, 70), (200, 7), (1, 1)] {
+                    let a = super::test_helpers::make_bitset(*left_size, left);
+                    let b = super::test_helpers::make_bitset(*right_size, right);
+
+                    let c = $trait::$method(&a, &b);
+                    assert_eq!(c.size, max(*left_size, *right_size));
+                    for i in 0..c.size {
+                        let l = i < a.size && a.get(i);
+                        let r = i < b.size && b.get(i);
+                        assert_eq!(c.get(i), $bit_operation(l, r));
+                    }
 
-        assert_eq!(BitSet::blocks_number(block_size), 1);
-        assert_eq!(BitSet::blocks_number(block_size + 1), 2);
+                    assert_eq!($trait::$method(a.clone(), &b), c);
+                    assert_eq!($trait::$method(&a, b.clone()), c);
+                    assert_eq!($trait::$method(a, b), c);
+                }
+                true
+            }
+        };
+    }
 
-        assert_eq!(BitSet::blocks_number(2 * block_size), 2);
-        assert_eq!(BitSet::blocks_number(2 * block_size + 1), 3);
+    check_bitset_operator!(check_bit_and_operator, BitAnd, bitand, |l, r| l && r);
+    check_bitset_operator!(check_bit_or_operator, BitOr, bitor, |l, r| l || r);
+    check_bitset_operator!(check_bit_xor_operator, BitXor, bitxor, |l, r| l ^ r);
+    check_bitset_operator!(check_sub_operator, Sub, sub, |l: bool, r: bool| l && !r);
+
+    #[test]
+    fn check_operators_reuse_owned_left_bitset() {
+        let a = super::test_helpers::make_bitset(200, 0xf0f0);
+        let b = super::test_helpers::make_bitset(70, 0x0ff0);
+        let expected = &a | &b;
+
+        let pointer = a.blocks.as_ptr();
+        let c = a | &b;
+        assert_eq!(c.blocks.as_ptr(), pointer);
+        assert_eq!(c, expected);
+
+        let mut d = super::test_helpers::make_bitset(200, 0xf0f0);
+        let pointer = d.blocks.as_ptr();
+        d &= b.clone();
+        assert_eq!(d.blocks.as_ptr(), pointer);
+        assert_eq!(d, super::test_helpers::make_bitset(200, 0xf0f0) & b);
     }
 
     #[test]
-    fn check_getting_number_of_bits_in_block() {
-        assert_eq!(BitSet::block_size(), size_of::<usize>() * 8);
+    fn check_operators_syntax() {
+        let a = BitSet::from(0b1100u8);
+        let b = BitSet::from(0b1010u8);
+        assert_eq!((&a & &b).to_string(), "00001000");
+        assert_eq!((&a | b.clone()).to_string(), "00001110");
+        assert_eq!((a.clone() ^ &b).to_string(), "00000110");
+        assert_eq!((a - b).to_string(), "00000100");
+    }
+
+    #[test]
+    fn check_bit_and_assign_operator() {
+        let mut a = BitSet::from(0b1100u8);
+        a &= BitSet::from(0b0000_0110_0000_0100u16);
+        assert_eq!(a.size, 16);
+        assert_eq!(a.to_string(), "0000000000000100");
+    }
+
+    /// Checks logical and function converting between bitsets of different sizes
+    macro_rules! check_logical_bit_and {
+        ($func:ident, $left:ty, $right:ty) => {
+            #[quickcheck]
+            fn $func(left: $left, right: $right) -> bool {
+                let left_size = size_of::<$left>() * 8;
+                let right_size = size_of::<$right>() * 8;
+
+                let a = BitSet::from(left);
+                let b = BitSet::from(right);
+                let c = a & b;
+                assert_eq!(c.size, max(left_size, right_size));
+                assert_eq!(
+                    u128::try_from(c).unwrap(),
+                    u128::from(left as u128 & right as u128)
+                );
+                true
+            }
+        };
+    }
+
+    /// Checks logical and function converting between bitsets of different sizes
+    macro_rules! check_logical_bit_and_for_refs {
+        ($func:ident, $left:ty, $right:ty) => {
+            #[quickcheck]
+            fn $func(left: $left, right: $right) -> bool {
+                let left_size = size_of::<$left>() * 8;
+                let right_size = size_of::<$right>() * 8;
+
+                let a = BitSet::from(left);
+                let b = BitSet::from(right);
+                let c = &a & &b;
+                assert_eq!(c.size, max(left_size, right_size));
+                assert_eq!(
+                    u128::try_from(c).unwrap(),
+                    u128::from(left as u128 & right as u128)
+                );
+                true
+            }
+        };
     }
+
+    check_logical_bit_and!(check_logical_bit_and_u8_u8, u8, u8);
+    check_logical_bit_and!(check_logical_bit_and_u8_u16, u8, u16);
+    check_logical_bit_and!(check_logical_bit_and_u8_u32, u8, u32);
+    check_logical_bit_and!(check_logical_bit_and_u8_u64, u8, u64);
+    check_logical_bit_and!(check_logical_bit_and_u8_u128, u8, u128);
+    check_logical_bit_and!(check_logical_bit_and_u8_usize, u8, usize);
+
+    check_logical_bit_and!(check_logical_bit_and_u16_u8,    u16, u8);
+    check_logical_bit_and!(check_logical_bit_and_u16_u16,   u16, u16);
+    check_logical_bit_and!(check_logical_bit_and_u16_u32,   u16, u32);
+    check_logical_bit_and!(check_logical_bit_and_u16_u64,   u16, u64);
+    check_logical_bit_and!(check_logical_bit_and_u16_u128,  u16, u128);
+    check_logical_bit_and!(check_logical_bit_and_u16_usize, u16, usize);
+
+    check_logical_bit_and!(check_logical_bit_and_u32_u8,    u32, u8);
+    check_logical_bit_and!(check_logical_bit_and_u32_u16,   u32, u16);
+    check_logical_bit_and!(check_logical_bit_and_u32_u32,   u32, u32);
+    check_logical_bit_and!(check_logical_bit_and_u32_u64,   u32, u64);
+    check_logical_bit_and!(check_logical_bit_and_u32_u128,  u32, u128);
+    check_logical_bit_and!(check_logical_bit_and_u32_usize, u32, usize);
+
+    check_logical_bit_and!(check_logical_bit_and_u64_u8,    u64, u8);
+    check_logical_bit_and!(check_logical_bit_and_u64_u16,   u64, u16);
+    check_logical_bit_and!(check_logical_bit_and_u64_u32,   u64, u32);
+    check_logical_bit_and!(check_logical_bit_and_u64_u64,   u64, u64);
+    check_logical_bit_and!(check_logical_bit_and_u64_u128,  u64, u128);
+    check_logical_bit_and!(check_logical_bit_and_u64_usize, u64, usize);
+
+    check_logical_bit_and!(check_logical_bit_and_u128_u8,    u128, u8);
+    check_logical_bit_and!(check_logical_bit_and_u128_u16,   u128, u16);
+    check_logical_bit_and!(check_logical_bit_and_u128_u32,   u128, u32);
+    check_logical_bit_and!(check_logical_bit_and_u128_u64,   u128, u64);
+    check_logical_bit_and!(check_logical_bit_and_u128_u128,  u128, u128);
+    check_logical_bit_and!(check_logical_bit_and_u128_usize, u128, usize);
+
+    check_logical_bit_and!(check_logical_bit_and_usize_u8,    usize, u8);
+    check_logical_bit_and!(check_logical_bit_and_usize_u16,   usize, u16);
+    check_logical_bit_and!(check_logical_bit_and_usize_u32,   usize, u32);
+    check_logical_bit_and!(check_logical_bit_and_usize_u64,   usize, u64);
+    check_logical_bit_and!(check_logical_bit_and_usize_u128,  usize, u128);
+    check_logical_bit_and!(check_logical_bit_and_usize_usize, usize, usize);
+
+
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u8_u8, u8, u8);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u8_u16, u8, u16);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u8_u32, u8, u32);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u8_u64, u8, u64);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u8_u128, u8, u128);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u8_usize, u8, usize);
+
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u16_u8,    u16, u8);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u16_u16,   u16, u16);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u16_u32,   u16, u32);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u16_u64,   u16, u64);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u16_u128,  u16, u128);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u16_usize, u16, usize);
+
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u32_u8,    u32, u8);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u32_u16,   u32, u16);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u32_u32,   u32, u32);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u32_u64,   u32, u64);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u32_u128,  u32, u128);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u32_usize, u32, usize);
+
+check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u64_u8,    u64, u8);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u64_u16,   u64, u16);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u64_u32,   u64, u32);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u64_u64,   u64, u64);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u64_u128,  u64, u128);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u64_usize, u64, usize);
+
+check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u128_u8,    u128, u8);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u128_u16,   u128, u16);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u128_u32,   u128, u32);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u128_u64,   u128, u64);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u128_u128,  u128, u128);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u128_usize, u128, usize);
+
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_usize_u8,    usize, u8);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_usize_u16,   usize, u16);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_usize_u32,   usize, u32);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_usize_u64,   usize, u64);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_usize_u128,  usize, u128);
+    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_usize_usize, usize, usize);
+
+    // Checks =& function converting between bitsets of different sizes
+    // macro_rules! check_bit_and_assign {
+    //     ($func:ident, $left:ty, $right:ty) => {
+    //         #[quickcheck]
+    //         fn $func(left: $left, right: $right) -> bool {
+    //             let left_size = size_of::<$left>() * 8;
+    //             let right_size = size_of::<$right>() * 8;
+
+    //             let mut a = BitSet::from(left);
+    //             let b = BitSet::from(right);
+    //             let a &= b;
+    //             assert_eq!(a.size, max(left_size, right_size));
+    //             assert_eq!(
+    //                 u128::try_from(a).unwrap(),
+    //                 u128::from(left as u128 & right as u128)
+    //             );
+    //             true
+    //         }
+    //     };
+    // }
+
+    // check_bit_and_assign!(check_logical_bit_and_assign_u8_u8, u8, u8);
+
 }
 
 #[cfg(test)]
-mod test_constructors {
-
+mod test_shift_operators {
+    use super::test_helpers::make_bitset;
     use super::*;
 
+    #[quickcheck]
+    fn check_shift_left(value: u128, shift: u8) -> bool {
+        let shift = shift as usize;
+        for size in &[1, 63, 64, 65, 70, 128, 200] {
+            let b = make_bitset(*size, value);
+            let c = &b << shift;
+            assert_eq!(c.size, *size);
+            for i in 0..*size {
+                let expected = i >= shift && b.get(i - shift);
+                assert_eq!(c.get(i), expected);
+            }
+        }
+        true
+    }
+
+    #[test]
+    fn check_shift_left_across_block_boundary() {
+        let block_size = BitSet::block_size();
+        let mut b = BitSet::new(3 * block_size);
+        b.set(0, true);
+        b.set(block_size - 1, true);
+        b.set(2 * block_size + 1, true);
+
+        b <<= 2;
+        assert_eq!(b.count(), 3);
+        assert!(b.get(2));
+        assert!(b.get(block_size + 1));
+        assert!(b.get(2 * block_size + 3));
+
+        let c = b << (block_size - 1);
+        assert_eq!(c.count(), 2);
+        assert!(c.get(block_size + 1));
+        assert!(c.get(2 * block_size));
+    }
+
+    #[test]
+    fn check_shift_left_by_whole_blocks() {
+        let block_size = BitSet::block_size();
+        let mut b = BitSet::new(3 * block_size + 5);
+        b.set(0, true);
+        b.set(3, true);
+        b.set(block_size + 3, true);
+
+        let c = &b << block_size;
+        assert_eq!(c.blocks[0], 0);
+        assert_eq!(c.blocks[1], b.blocks[0]);
+        assert_eq!(c.blocks[2], b.blocks[1]);
+        assert_eq!(c.blocks[3], 0);
+
+        let d = &b << (2 * block_size);
+        assert_eq!(d.blocks[..2], [0, 0]);
+        assert_eq!(d.blocks[2], b.blocks[0]);
+        assert_eq!(d.blocks[3], b.blocks[1]);
+
+        // the top block contains only 5 bits, so the higher bits are dropped
+        b <<= 3 * block_size;
+        assert_eq!(b.count(), 2);
+        assert_eq!(b.blocks[3], 0b1001);
+    }
+
+    #[test]
+    fn check_shift_left_by_size_clears_the_set() {
+        let b = make_bitset(70, u128::MAX);
+        assert_eq!((&b << 69).count(), 1);
+        assert_eq!((&b << 70).count(), 0);
+        assert_eq!((&b << usize::MAX).count(), 0);
+        assert_eq!((b << 0).count(), 70);
+    }
+
+    #[quickcheck]
+    fn check_shift_right(value: u128, shift: u8) -> bool {
+        let shift = shift as usize;
+        for size in &[1, 63, 64, 65, 70, 128, 200] {
+            let b = make_bitset(*size, value);
+            let c = &b >> shift;
+            assert_eq!(c.size, *size);
+            for i in 0..*size {
+                let expected = i + shift < *size && b.get(i + shift);
+                assert_eq!(c.get(i), expected);
+            }
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_shift_left_and_right_clears_top_bits(value: u128, shift: u8) -> bool {
+        let shift = shift as usize;
+        for size in &[1, 63, 64, 65, 70, 128, 200] {
+            let b = make_bitset(*size, value);
+            let c = (&b << shift) >> shift;
+            for i in 0..*size {
+                let expected = i + shift < *size && b.get(i);
+                assert_eq!(c.get(i), expected);
+            }
+        }
+        true
+    }
+
     #[test]
-    #[should_panic(expected = "Creating BitSet with zero bits is not allowed.")]
-    fn check_creating_bitset_with_zero_bits() {
-        BitSet::new(0);
+    fn check_shift_right_by_whole_blocks() {
+        let block_size = BitSet::block_size();
+        let mut b = BitSet::new(3 * block_size + 5);
+        b.set(0, true);
+        b.set(block_size + 3, true);
+        b.set(2 * block_size, true);
+        b.set(3 * block_size + 4, true);
+
+        let c = &b >> block_size;
+        assert_eq!(c.blocks[0], b.blocks[1]);
+        assert_eq!(c.blocks[1], b.blocks[2]);
+        assert_eq!(c.blocks[2], b.blocks[3]);
+        assert_eq!(c.blocks[3], 0);
+
+        b >>= 3 * block_size;
+        assert_eq!(b.count(), 1);
+        assert_eq!(b.blocks, [0b10000, 0, 0, 0]);
     }
 
     #[test]
-    fn check_creating_new_bitset() {
-        let block_size = size_of::<usize>() * 8;
-
-        // for 1 bit we should have 1 block
-        let a = BitSet::new(1);
-        assert_eq!(a.blocks.len(), 1);
-        assert_eq!(a.size, 1);
-
-        // for max bits in one block, we should have one block
-        let b = BitSet::new(block_size);
-        assert_eq!(b.blocks.len(), 1);
-        assert_eq!(b.size, block_size);
-
-        // for 1 + max bits in one block, we should have two block
-        let c = BitSet::new(block_size + 1);
-        assert_eq!(c.blocks.len(), 2);
-        assert_eq!(c.size, block_size + 1);
+    fn check_shift_right_by_size_clears_the_set() {
+        let b = make_bitset(70, u128::MAX);
+        assert_eq!((&b >> 69).count(), 1);
+        assert_eq!((&b >> 70).count(), 0);
+        assert_eq!((&b >> usize::MAX).count(), 0);
+        assert_eq!((b >> 0).count(), 70);
     }
 }
 
 #[cfg(test)]
-#[macro_use]
-mod test_conversions_to_types {
-
+mod test_bit_order_functions {
+    use super::test_helpers::{make_bitset, SIZES};
     use super::*;
 
-    #[test]
-    fn check_string_conversion() {
-        let b = BitSet::new(1);
-        assert_eq!(b.to_string(), "0");
-
-        let mut c = BitSet::new(1);
-        c.set(0, true);
-        assert_eq!(c.to_string(), "1");
-
-        let mut d = BitSet::new(66);
-        d.set(0, true);
-        d.set(5, true);
-        d.set(64, true);
-        //                    60        50        40        30        20        10         0
-        //                543210987654321098765432109876543210987654321098765432109876543210
-        let expected_d = "010000000000000000000000000000000000000000000000000000000000100001";
-        assert_eq!(d.to_string(), expected_d);
+    #[quickcheck]
+    fn check_shift_left_carry(value: u128, n: u8) -> bool {
+        let n = n as usize;
+        for size in SIZES {
+            let original = make_bitset(*size, value);
+            let mut shifted = original.clone();
+            let carry = shifted.shift_left_carry(n);
+            assert_eq!(shifted, &original << n);
+            for i in 0..*size {
+                assert_eq!(carry.get(i), i + n >= *size && original.get(i));
+            }
+            assert_eq!((shifted >> n) | carry, original);
+        }
+        true
     }
 
-    /// Checks conversion from different values
-    macro_rules! check_type_conversion {
-        ($func:ident, $from:ty, $to:ty) => {
-            #[quickcheck]
-            fn $func(value: $from) -> bool {
-                let bitset = BitSet::from(value);
-
-                let new_value = <$to>::try_from(bitset);
-                if value as $from <= <$to>::MAX as $from {
-                    // conversion should be fine
-                    assert_eq!(value as $to, new_value.unwrap());
-                } else {
-                    // in this case the value is too large
-                    assert_eq!(
-                        new_value,
-                        Err("Value stored in BitSet cannot be converted to u8.")
-                    );
-                }
-                true
+    #[quickcheck]
+    fn check_shift_right_carry(value: u128, n: u8) -> bool {
+        let n = n as usize;
+        for size in SIZES {
+            let original = make_bitset(*size, value);
+            let mut shifted = original.clone();
+            let carry = shifted.shift_right_carry(n);
+            assert_eq!(shifted, &original >> n);
+            for i in 0..*size {
+                assert_eq!(carry.get(i), i < n && original.get(i));
             }
-        };
+            assert_eq!((shifted << n) | carry, original);
+        }
+        true
     }
 
-    check_type_conversion! {check_conversion_from_u8_to_u8, u8, u8}
-    check_type_conversion! {check_conversion_from_u16_to_u8, u16, u8}
-    check_type_conversion! {check_conversion_from_u32_to_u8, u32, u8}
-    check_type_conversion! {check_conversion_from_u64_to_u8, u64, u8}
-    check_type_conversion! {check_conversion_from_u128_to_u8, u128, u8}
-    check_type_conversion! {check_conversion_from_usize_to_u8, usize, u8}
-
-    check_type_conversion! {check_conversion_from_u8_to_u16, u8, u16}
-    check_type_conversion! {check_conversion_from_u16_to_u16, u16, u16}
-    check_type_conversion! {check_conversion_from_u32_to_u16, u32, u16}
-    check_type_conversion! {check_conversion_from_u64_to_u16, u64, u16}
-    check_type_conversion! {check_conversion_from_u128_to_u16, u128, u16}
-    check_type_conversion! {check_conversion_from_usize_to_u16, usize, u16}
-
-    check_type_conversion! {check_conversion_from_u8_to_u32, u8, u32}
-    check_type_conversion! {check_conversion_from_u16_to_u32, u16, u32}
-    check_type_conversion! {check_conversion_from_u32_to_u32, u32, u32}
-    check_type_conversion! {check_conversion_from_u64_to_u32, u64, u32}
-    check_type_conversion! {check_conversion_from_u128_to_u32, u128, u32}
-    check_type_conversion! {check_conversion_from_usize_to_u32, usize, u32}
+    #[test]
+    fn check_shift_carry_by_size_returns_whole_bitset() {
+        let original = make_bitset(70, u128::MAX);
 
-    check_type_conversion! {check_conversion_from_u8_to_u64, u8, u64}
-    check_type_conversion! {check_conversion_from_u16_to_u64, u16, u64}
-    check_type_conversion! {check_conversion_from_u32_to_u64, u32, u64}
-    check_type_conversion! {check_conversion_from_u64_to_u64, u64, u64}
-    check_type_conversion! {check_conversion_from_u128_to_u64, u128, u64}
-    check_type_conversion! {check_conversion_from_usize_to_u64, usize, u64}
+        let mut shifted = original.clone();
+        assert_eq!(shifted.shift_left_carry(usize::MAX), original);
+        assert!(shifted.is_empty());
 
-    check_type_conversion! {check_conversion_from_u8_to_u128, u8, u128}
-    check_type_conversion! {check_conversion_from_u16_to_u128, u16, u128}
-    check_type_conversion! {check_conversion_from_u32_to_u128, u32, u128}
-    check_type_conversion! {check_conversion_from_u64_to_u128, u64, u128}
-    check_type_conversion! {check_conversion_from_u128_to_u128, u128, u128}
-    check_type_conversion! {check_conversion_from_usize_to_u128, usize, u128}
+        let mut shifted = original.clone();
+        assert_eq!(shifted.shift_right_carry(70), original);
+        assert!(shifted.is_empty());
 
-    check_type_conversion! {check_conversion_from_u8_to_usize, u8, usize}
-    check_type_conversion! {check_conversion_from_u16_to_usize, u16, usize}
-    check_type_conversion! {check_conversion_from_u32_to_usize, u32, usize}
-    check_type_conversion! {check_conversion_from_u64_to_usize, u64, usize}
-    check_type_conversion! {check_conversion_from_u128_to_usize, u128, usize}
-    check_type_conversion! {check_conversion_from_usize_to_usize, usize, usize}
-}
+        let mut shifted = original.clone();
+        assert!(shifted.shift_left_carry(0).is_empty());
+        assert_eq!(shifted, original);
+    }
 
-#[cfg(test)]
-#[macro_use]
-mod test_conversions_from_types {
+    #[quickcheck]
+    fn check_rotate_left(value: u128, n: u8) -> bool {
+        let n = n as usize;
+        for size in &[1, 63, 64, 65, 70, 128, 200] {
+            let b = make_bitset(*size, value);
+            let mut c = b.clone();
+            c.rotate_left(n);
+            for i in 0..*size {
+                assert_eq!(c.get((i + n) % size), b.get(i));
+            }
+        }
+        true
+    }
 
-    use super::*;
+    #[quickcheck]
+    fn check_rotate_left_by_size_is_identity(value: u128) -> bool {
+        for size in &[1, 63, 64, 65, 70, 128, 200] {
+            let b = make_bitset(*size, value);
+            let mut c = b.clone();
+            c.rotate_left(*size);
+            assert_eq!(c, b);
+            c.rotate_right(*size);
+            assert_eq!(c, b);
+        }
+        true
+    }
 
-    /// Checks conversion from different values
-    macro_rules! check_type_conversion {
-        ($func:ident, $t:ty) => {
-            #[quickcheck]
-            fn $func(value: $t) -> bool {
-                let bitset = BitSet::from(value);
-                let value_bits_count = size_of::<$t>() * 8;
+    #[quickcheck]
+    fn check_rotate_left_and_right_restores(value: u128, n: u16) -> bool {
+        let n = n as usize;
+        for size in &[1, 63, 64, 65, 70, 128, 200] {
+            let b = make_bitset(*size, value);
+            let mut c = b.clone();
+            c.rotate_left(n);
+            c.rotate_right(n);
+            assert_eq!(c, b);
+        }
+        true
+    }
 
-                // the bitset should have the same number of bits as the initial value
-                assert_eq!(bitset.size, value_bits_count);
+    #[quickcheck]
+    fn check_reverse(value: u128) -> bool {
+        for size in &[1, 7, 63, 64, 65, 70, 128, 129, 200] {
+            let b = make_bitset(*size, value);
+            let mut c = b.clone();
+            c.reverse();
+            assert_eq!(c.size, *size);
+            for i in 0..*size {
+                assert_eq!(c.get(i), b.get(size - 1 - i));
+            }
+            // no bits are moved outside the size
+            let mut d = c.clone();
+            d.clear_unused_bits();
+            assert_eq!(d, c);
 
-                // converting both to a string should give the same result
-                let value_bits = format!("{:0width$b}", value, width = value_bits_count);
-                let bitset_bits = bitset.to_string();
-                assert_eq! {value_bits, bitset_bits}
+            c.reverse();
+            assert_eq!(c, b);
+        }
+        true
+    }
 
-                // also, check bit by bit that everything is the same
-                for bit in 0..value_bits_count - 1 {
-                    let bit_from_value: bool = (value & (1 << bit) != 0);
-                    assert_eq! {bit_from_value, bitset.get(bit)}
-                }
-                true
+    #[quickcheck]
+    fn check_rotate_each_block(value: u128, n: u8) -> bool {
+        let n = n as usize;
+        let block_size = BitSet::block_size();
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let mut c = b.clone();
+            c.rotate_each_block(n);
+            assert_eq!(c.size, *size);
+            assert_eq!(c.count(), b.count());
+
+            for i in 0..*size {
+                let block_start = i / block_size * block_size;
+                let block_bits = min(block_size, size - block_start);
+                let expected_position = block_start + (i - block_start + n) % block_bits;
+                assert_eq!(c.get(expected_position), b.get(i));
             }
-        };
+        }
+        true
     }
 
     #[test]
-    fn check_conversion_from_u8_value() {
-        let b = BitSet::from(0 as u8);
-        assert_eq!(b.size, 8);
-        assert_eq!(b.blocks.len(), 1);
-        assert_eq!(b.to_string(), "00000000");
-
-        let b = BitSet::from(u8::MAX);
-        assert_eq!(b.size, 8);
-        assert_eq!(b.blocks.len(), 1);
-        assert_eq!(b.to_string(), "11111111");
+    fn check_rotate_each_block_keeps_bits_in_blocks() {
+        let block_size = BitSet::block_size();
+        let mut b = BitSet::new(2 * block_size);
+        b.set(block_size - 1, true);
+        b.set(2 * block_size - 2, true);
+
+        b.rotate_each_block(1);
+        assert_eq!(b.blocks, [1, 1 << (block_size - 1)]);
+        b.rotate_each_block(block_size);
+        assert_eq!(b.blocks, [1, 1 << (block_size - 1)]);
+    }
 
-        let b = BitSet::from(u8::from(170));
-        assert_eq!(b.size, 8);
-        assert_eq!(b.blocks.len(), 1);
-        assert_eq!(b.to_string(), "10101010");
+    #[test]
+    fn check_is_palindrome() {
+        // odd size
+        let mut b = BitSet::new(65);
+        assert!(b.is_palindrome());
+        b.set(0, true);
+        assert!(!b.is_palindrome());
+        b.set(64, true);
+        assert!(b.is_palindrome());
+        b.set(32, true);
+        assert!(b.is_palindrome());
+        b.set(31, true);
+        assert!(!b.is_palindrome());
+
+        // even size
+        let mut b = BitSet::new(70);
+        b.set(1, true);
+        b.set(68, true);
+        assert!(b.is_palindrome());
+        b.set(34, true);
+        assert!(!b.is_palindrome());
+        b.set(35, true);
+        assert!(b.is_palindrome());
+
+        assert!(BitSet::new(1).is_palindrome());
     }
 
-    // // Test converting from different values;
-    check_type_conversion! {check_conversion_from_u8, u8}
-    check_type_conversion! {check_conversion_from_u16, u16}
-    check_type_conversion! {check_conversion_from_u32, u32}
-    check_type_conversion! {check_conversion_from_u64, u64}
-    check_type_conversion! {check_conversion_from_u128, u128}
-    check_type_conversion! {check_conversion_from_usize, usize}
-}
+    #[quickcheck]
+    fn check_is_palindrome_for_random_values(value: u128) -> bool {
+        for size in &[1, 2, 7, 63, 64, 65, 70, 128, 200] {
+            let b = make_bitset(*size, value);
+            let expected = (0..*size).all(|i| b.get(i) == b.get(size - 1 - i));
+            assert_eq!(b.is_palindrome(), expected);
+
+            // a bitset with its reversed copy appended is always a palindrome
+            let mut c = b.clone();
+            c.reverse();
+            let mut d = b.clone();
+            d.append(&c);
+            assert!(d.is_palindrome());
+        }
+        true
+    }
 
-#[cfg(test)]
-mod test_basic_getter_and_setter {
-    use super::*;
+    #[test]
+    #[should_panic(expected = "Permutation length 3 is different than the size 4.")]
+    fn check_permuting_with_too_short_permutation() {
+        BitSet::new(4).permute(&[0, 1, 2]);
+    }
 
     #[test]
-    #[should_panic(expected = "Bit position [256] is outside available range: [0, 255]")]
-    fn check_using_setter_for_too_large_position() {
-        let mut b = BitSet::new(256);
-        b.set(256, false);
+    #[should_panic(
+        expected = "Permutation position [4] is repeated or outside available range: [0, 3]"
+    )]
+    fn check_permuting_with_too_large_position() {
+        BitSet::new(4).permute(&[0, 1, 2, 4]);
     }
 
     #[test]
-    #[should_panic(expected = "Bit position [256] is outside available range: [0, 255]")]
-    fn check_using_getter_for_too_large_position() {
-        let b = BitSet::new(256);
-        b.get(256);
+    #[should_panic(
+        expected = "Permutation position [1] is repeated or outside available range: [0, 3]"
+    )]
+    fn check_permuting_with_repeated_position() {
+        BitSet::new(4).permute(&[0, 1, 2, 1]);
     }
 
     #[test]
-    fn check_simple_operations() {
-        let mut b = BitSet::new(4);
-        assert_eq!(b.get(0), false);
-        assert_eq!(b.get(1), false);
-        assert_eq!(b.get(2), false);
-        assert_eq!(b.get(3), false);
+    fn check_permuting() {
+        let b = BitSet::from(0b0000_0011_u8);
+        let c = b.permute(&[7, 0, 6, 1, 5, 2, 4, 3]);
+        assert_eq!(c.to_string(), "00001010");
 
-        b.set(0, true);
-        assert_eq!(b.get(0), true);
-        assert_eq!(b.get(1), false);
-        assert_eq!(b.get(2), false);
-        assert_eq!(b.get(3), false);
+        let c = b.permute(&[2, 3, 4, 5, 6, 7, 0, 1]);
+        assert_eq!(c.to_string(), "11000000");
+    }
 
-        b.set(3, true);
-        assert_eq!(b.get(0), true);
-        assert_eq!(b.get(1), false);
-        assert_eq!(b.get(2), false);
-        assert_eq!(b.get(3), true);
+    #[quickcheck]
+    fn check_permuting_with_identity(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let identity: Vec<usize> = (0..*size).collect();
+            assert_eq!(b.permute(&identity), b);
+
+            // the reversing permutation
+            let reversing: Vec<usize> = (0..*size).rev().collect();
+            let mut reversed = b.clone();
+            reversed.reverse();
+            assert_eq!(b.permute(&reversing), reversed);
+        }
+        true
+    }
 
-        b.set(1, false);
-        b.set(3, false);
-        assert_eq!(b.get(0), true);
-        assert_eq!(b.get(1), false);
-        assert_eq!(b.get(2), false);
-        assert_eq!(b.get(3), false);
+    #[test]
+    fn check_rotate_right_moves_lowest_bit_to_top() {
+        let mut b = BitSet::new(70);
+        b.set(0, true);
+        b.set(1, true);
+
+        b.rotate_right(1);
+        assert!(b.get(0));
+        assert!(b.get(69));
+        assert_eq!(b.count(), 2);
     }
 }
 
 #[cfg(test)]
-#[macro_use]
-mod test_utitily_functions {
-
+mod test_comparison_functions {
+    use super::test_helpers::{make_bitset, SIZES};
     use super::*;
 
     #[test]
-    fn check_all_function() {
-        let mut b = BitSet::new(300);
-        assert_eq! {b.all(), false}
-        b.set(10, true);
-        assert_eq! {b.all(), false}
+    fn check_eq_bits() {
+        let mut short = make_bitset(64, 0xF0F0);
+        let mut long = make_bitset(70, 0xF0F0);
+        long.set(69, true);
+
+        assert!(short.eq_bits(&long, 64));
+        assert!(long.eq_bits(&short, 64));
+        assert!(short.eq_bits(&long, 0));
+        assert_ne!(short, long);
+
+        short.set(63, true);
+        assert!(!short.eq_bits(&long, 64));
+        assert!(short.eq_bits(&long, 63));
+        long.set(63, true);
+        assert!(short.eq_bits(&long, 64));
+
+        long.set(0, true);
+        assert!(!short.eq_bits(&long, 1));
+    }
 
-        let mut b = BitSet::from(u128::MAX);
-        assert_eq! {b.all(), true}
-        b.set(10, false);
-        assert_eq! {b.all(), false}
+    #[quickcheck]
+    fn check_eq_bits_for_random_values(left: u128, right: u128, len: u8) -> bool {
+        for size in SIZES {
+            let a = make_bitset(*size, left);
+            let b = make_bitset(size + 10, right);
+            let len = len as usize % (size + 1);
+            let expected = (0..len).all(|i| a.get(i) == b.get(i));
+            assert_eq!(a.eq_bits(&b, len), expected);
+            assert_eq!(b.eq_bits(&a, len), expected);
+        }
+        true
     }
 
     #[test]
-    fn check_any_function() {
-        let mut b = BitSet::new(300);
-        assert_eq! {b.any(), false}
-        b.set(10, true);
-        assert_eq! {b.any(), true}
-
-        let mut b = BitSet::from(u128::MAX);
-        assert_eq! {b.any(), true}
-        b.set(10, false);
-        assert_eq! {b.any(), true}
+    #[should_panic(expected = "Bit range [0, 65) is outside available range: [0, 63]")]
+    fn check_eq_bits_with_len_outside_size() {
+        BitSet::new(70).eq_bits(&BitSet::new(64), 65);
     }
 
     #[test]
-    fn check_count_function() {
-        let mut b = BitSet::new(300);
-        assert_eq! {b.count(), 0}
-        b.set(10, true);
-        assert_eq! {b.count(), 1}
+    fn check_subset_functions() {
+        let mut small = BitSet::new(70);
+        small.set(3, true);
+        let mut large = small.clone();
+        large.set(69, true);
+
+        assert!(small.is_subset(&large));
+        assert!(small.is_proper_subset(&large));
+        assert!(!small.is_superset(&large));
+        assert!(large.is_superset(&small));
+        assert!(large.is_proper_superset(&small));
+        assert!(!large.is_subset(&small));
+
+        // a bitset is a subset of itself, but not a proper one
+        assert!(small.is_subset(&small));
+        assert!(small.is_superset(&small));
+        assert!(!small.is_proper_subset(&small));
+        assert!(!small.is_proper_superset(&small));
+
+        // the empty bitset is a subset of all of them
+        let empty = BitSet::new(70);
+        assert!(empty.is_proper_subset(&small));
+        assert!(empty.is_subset(&empty));
+    }
 
-        let mut b = BitSet::from(u128::MAX);
-        assert_eq! {b.count(), 128}
-        b.set(10, false);
-        assert_eq! {b.count(), 127}
+    #[quickcheck]
+    fn check_subset_functions_for_random_values(left: u128, right: u128) -> bool {
+        for size in SIZES {
+            let a = make_bitset(*size, left);
+            let b = make_bitset(*size, right);
+            let expected = (0..*size).all(|i| !a.get(i) || b.get(i));
+            assert_eq!(a.is_subset(&b), expected);
+            assert_eq!(b.is_superset(&a), expected);
+            assert_eq!(a.is_proper_subset(&b), expected && a != b);
+            assert_eq!(b.is_proper_superset(&a), expected && a != b);
+        }
+        true
     }
 
-    /// Checks conversion from different values
-    macro_rules! check_enlarge_function {
-        ($func:ident, $t:ty) => {
-            #[quickcheck]
-            fn $func(value: $t) -> bool {
-                let initial_size = size_of::<$t>() * 8;
-                let new_size = value as usize;
+    #[test]
+    #[should_panic(expected = "BitSets have different sizes: 70 and 71.")]
+    fn check_is_subset_with_different_size() {
+        BitSet::new(70).is_subset(&BitSet::new(71));
+    }
 
-                let mut b = BitSet::from(value);
-                assert_eq!(b.size, initial_size);
+    #[quickcheck]
+    fn check_hamming_distance(left: u128, right: u128) -> bool {
+        for size in SIZES {
+            let a = make_bitset(*size, left);
+            let b = make_bitset(*size, right);
+            let mut xor = a.clone();
+            xor.symmetric_difference(&b);
 
-                let res: Result<(), BitSetError> = b.enlarge(new_size as usize);
+            assert_eq!(a.hamming_distance(&b), xor.count());
+            assert_eq!(a.hamming_distance(&a), 0);
+        }
+        true
+    }
 
-                if new_size <= initial_size {
-                    let expected = BitSetError::EnlargeError {
-                        from: initial_size,
-                        to: new_size,
-                    };
-                    let error = res.err().unwrap();
-                    assert_eq!(expected, error);
-                } else {
-                    assert!(res.is_ok());
-                    assert_eq!(b.size, new_size);
-                    assert_eq!($t::try_from(b).unwrap(), value);
-                }
-                true
-            }
-        };
+    #[test]
+    #[should_panic(expected = "BitSets have different sizes: 70 and 71.")]
+    fn check_hamming_distance_with_different_size() {
+        BitSet::new(70).hamming_distance(&BitSet::new(71));
     }
 
-    check_enlarge_function! {check_enlarge_function_for_u8, u8}
-    check_enlarge_function! {check_enlarge_function_for_u16, u16}
-    check_enlarge_function! {check_enlarge_function_for_u32, u32}
-    check_enlarge_function! {check_enlarge_function_for_u64, u64}
-    check_enlarge_function! {check_enlarge_function_for_u128, u128}
-    check_enlarge_function! {check_enlarge_function_for_usize, usize}
-}
+    #[quickcheck]
+    fn check_intersection_and_union_count(left: u128, right: u128) -> bool {
+        for size in SIZES {
+            let a = make_bitset(*size, left);
+            let b = make_bitset(*size, right);
+            let mut intersection = a.clone();
+            intersection.intersect(&b);
+            let mut union = a.clone();
+            union.union(&b);
+
+            assert_eq!(a.intersection_count(&b), intersection.count());
+            assert_eq!(a.union_count(&b), union.count());
+        }
+        true
+    }
 
-/// The returned bitset has the size of the larger one.
-/// However, it assumes the smaller has zeros when enlarged
-impl BitAnd for BitSet {
-    type Output = BitSet;
+    #[quickcheck]
+    fn check_symmetric_difference_count(left: u128, right: u128) -> bool {
+        for size in SIZES {
+            let a = make_bitset(*size, left);
+            let b = make_bitset(*size, right);
+            let count = a.symmetric_difference_count(&b);
+            let intersection = a.intersection_count(&b);
+
+            assert_eq!(count, a.union_count(&b) - intersection);
+            assert_eq!(count, a.count() + b.count() - 2 * intersection);
+            assert_eq!(count, a.hamming_distance(&b));
+        }
+        true
+    }
 
-    fn bitand(self, rhs: BitSet) -> Self::Output {
-        let lhs = self;
+    #[test]
+    fn check_jaccard_similarity() {
+        let mut a = BitSet::new(100);
+        let mut b = BitSet::new(100);
+        // both empty
+        assert_eq!(a.jaccard_similarity(&b), 1.0);
+
+        // disjoint
+        a.set(1, true);
+        a.set(70, true);
+        b.set(2, true);
+        b.set(99, true);
+        assert_eq!(a.intersection_count(&b), 0);
+        assert_eq!(a.union_count(&b), 4);
+        assert_eq!(a.jaccard_similarity(&b), 0.0);
+
+        // overlapping
+        b.set(70, true);
+        assert_eq!(a.intersection_count(&b), 1);
+        assert_eq!(a.union_count(&b), 4);
+        assert_eq!(a.jaccard_similarity(&b), 0.25);
+
+        // identical
+        assert_eq!(a.jaccard_similarity(&a), 1.0);
+    }
 
-        let output_size = max(lhs.size, rhs.size);
+    #[test]
+    #[should_panic(expected = "BitSets have different sizes: 70 and 71.")]
+    fn check_jaccard_similarity_with_different_size() {
+        BitSet::new(70).jaccard_similarity(&BitSet::new(71));
+    }
 
-        let mut blocks: Vec<usize> = vec![];
+    #[test]
+    fn check_is_bit_prefix_of() {
+        let mut short = BitSet::new(5);
+        short.set(0, true);
+        short.set(4, true);
+
+        let mut long = BitSet::new(200);
+        long.set(0, true);
+        long.set(4, true);
+        assert!(short.is_bit_prefix_of(&long));
+        assert!(short.is_bit_prefix_of(&short));
+
+        // bits above the prefix don't matter
+        long.set(5, true);
+        long.set(199, true);
+        assert!(short.is_bit_prefix_of(&long));
+
+        long.set(4, false);
+        assert!(!short.is_bit_prefix_of(&long));
+
+        // a longer bitset is never a prefix
+        assert!(!long.is_bit_prefix_of(&short));
+    }
 
-        for item in lhs.blocks.iter().zip_longest(rhs.blocks.iter()) {
-            match item {
-                Both(l, r) => {
-                    blocks.push(l & r);
-                }
-                Left(_) | Right(_) => blocks.push(0),
+    #[quickcheck]
+    fn check_is_bit_prefix_of_for_different_sizes(value: u128, other: u128) -> bool {
+        for short_size in &[1, 7, 63, 64, 65, 70, 128] {
+            for long_size in &[64, 65, 70, 128, 200] {
+                let short = make_bitset(*short_size, value);
+                let long = make_bitset(*long_size, other);
+                let expected = short_size <= long_size
+                    && (0..*short_size).all(|i| short.get(i) == long.get(i));
+                assert_eq!(short.is_bit_prefix_of(&long), expected);
+
+                // the same low bits make a prefix
+                let same = make_bitset(*long_size, value);
+                assert_eq!(short.is_bit_prefix_of(&same), short_size <= long_size);
             }
         }
-        BitSet {
-            blocks,
-            size: output_size,
+        true
+    }
+    #[test]
+    #[should_panic(expected = "BitSets have different sizes: 70 and 71.")]
+    fn check_first_difference_with_different_size() {
+        BitSet::new(70).first_difference(&BitSet::new(71));
+    }
+
+    #[test]
+    fn check_first_difference() {
+        let mut a = BitSet::new(200);
+        a.set(3, true);
+        a.set(150, true);
+        let mut b = a.clone();
+        assert_eq!(a.first_difference(&b), None);
+
+        b.set(0, true);
+        assert_eq!(a.first_difference(&b), Some(0));
+        assert_eq!(b.first_difference(&a), Some(0));
+
+        b.set(0, false);
+        b.set(100, true);
+        assert_eq!(a.first_difference(&b), Some(100));
+
+        b.set(100, false);
+        b.set(150, false);
+        assert_eq!(a.first_difference(&b), Some(150));
+    }
+
+    #[quickcheck]
+    fn check_first_difference_for_random_values(left: u128, right: u128) -> bool {
+        for size in SIZES {
+            let a = make_bitset(*size, left);
+            let b = make_bitset(*size, right);
+            let expected = (0..*size).find(|i| a.get(*i) != b.get(*i));
+            assert_eq!(a.first_difference(&b), expected);
         }
+        true
     }
 }
 
-impl BitAnd for &BitSet {
-    type Output = BitSet;
+#[cfg(test)]
+mod test_rank_and_select_functions {
+    use super::test_helpers::{make_bitset, SIZES};
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Bit range [0, 71) is outside available range: [0, 69]")]
+    fn check_rank_for_too_large_position() {
+        BitSet::new(70).rank(71);
+    }
 
-    fn bitand(self, rhs: &BitSet) -> Self::Output {
-        let lhs = self;
+    #[test]
+    fn check_rank() {
+        let mut b = BitSet::new(70);
+        b.set(0, true);
+        b.set(64, true);
+        b.set(69, true);
+
+        assert_eq!(b.rank(0), 0);
+        assert_eq!(b.rank(1), 1);
+        assert_eq!(b.rank(64), 1);
+        assert_eq!(b.rank(65), 2);
+        assert_eq!(b.rank(69), 2);
+        assert_eq!(b.rank(70), 3);
+    }
 
-        let output_size = max(lhs.size, rhs.size);
+    #[quickcheck]
+    fn check_rank_for_random_values(value: u128, position: u8) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let position = position as usize % (size + 1);
+            let expected = (0..position).filter(|i| b.get(*i)).count();
+            assert_eq!(b.rank(position), expected);
+            assert_eq!(b.rank(*size), b.count());
+        }
+        true
+    }
+    #[test]
+    fn check_select() {
+        let mut b = BitSet::new(200);
+        assert_eq!(b.select(0), None);
 
-        let mut blocks: Vec<usize> = vec![];
+        b.set(0, true);
+        b.set(64, true);
+        b.set(199, true);
+        assert_eq!(b.select(0), Some(0));
+        assert_eq!(b.select(1), Some(64));
+        assert_eq!(b.select(2), Some(199));
+        assert_eq!(b.select(3), None);
+        assert_eq!(b.select(usize::MAX), None);
+    }
 
-        for item in lhs.blocks.iter().zip_longest(rhs.blocks.iter()) {
-            match item {
-                Both(l, r) => {
-                    blocks.push(l & r);
+    #[quickcheck]
+    fn check_select_for_random_values(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            for i in 0..*size {
+                if b.get(i) {
+                    assert_eq!(b.select(b.rank(i)), Some(i));
                 }
-                Left(_) | Right(_) => blocks.push(0),
             }
+            assert_eq!(b.select(b.count()), None);
         }
-        BitSet {
-            blocks,
-            size: output_size,
-        }
+        true
     }
 }
 
-impl BitAndAssign for BitSet {
+#[cfg(test)]
+mod test_raw_blocks_functions {
+    use super::test_helpers::{make_bitset, SIZES};
+    use super::*;
 
-    fn bitand_assign(&mut self, rhs: Self) {
-        let lhs = self;
+    #[test]
+    #[should_panic(expected = "Block number [5] is outside available range: [0, 4]")]
+    fn check_splitting_blocks_at_too_large_block() {
+        let b = BitSet::new(4 * BitSet::block_size());
+        b.split_blocks_at(5);
+    }
+
+    #[test]
+    fn check_splitting_blocks() {
+        let size = 4 * BitSet::block_size() - 3;
+        let b = make_bitset(size, 0xdead_beef_1234_5678_9abc_def0);
+        for block_number in 0..=4 {
+            let (left, right) = b.split_blocks_at(block_number);
+            assert_eq!(left.len(), block_number);
+            assert_eq!(right.len(), 4 - block_number);
+            assert_eq!([left, right].concat(), b.blocks);
+        }
+    }
 
-        let output_size = max(lhs.size, rhs.size);
+    #[test]
+    fn check_raw_blocks() {
+        for size in SIZES {
+            let mut b = BitSet::new(*size);
+            b.set(0, true);
+            b.set(size - 1, true);
+            assert_eq!(b.raw_blocks().len(), BitSet::blocks_number(*size));
+            assert_eq!(b.bits_size(), *size);
+            assert_eq!(b.raw_blocks()[0] & 1, 1);
+        }
 
-        let mut blocks: Vec<usize> = vec![];
+        let b = BitSet::from(0b1011_0001u8);
+        assert_eq!(b.raw_blocks(), &[0b1011_0001]);
+    }
 
-        for item in lhs.blocks.iter().zip_longest(rhs.blocks.iter()) {
-            match item {
-                Both(l, r) => {
-                    blocks.push(l & r);
-                }
-                Left(_) | Right(_) => blocks.push(0),
-            }
+    #[test]
+    fn check_to_u64_vec() {
+        let mut b = BitSet::new(100);
+        b.set_all(true);
+        let words = b.to_u64_vec();
+        assert_eq!(words, vec![u64::MAX, (1 << 36) - 1]);
+
+        let mut b = BitSet::new(100);
+        b.set(0, true);
+        b.set(63, true);
+        b.set(64, true);
+        b.set(99, true);
+        assert_eq!(b.to_u64_vec(), vec![1 << 63 | 1, 1 << 35 | 1]);
+
+        assert_eq!(BitSet::new(1).to_u64_vec(), vec![0]);
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[quickcheck]
+    fn check_to_u64_vec_matches_raw_blocks(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let blocks: Vec<u64> = b.raw_blocks().iter().map(|block| *block as u64).collect();
+            assert_eq!(b.to_u64_vec(), blocks);
         }
-        lhs.blocks = blocks;
+        true
     }
 
+    #[quickcheck]
+    fn check_from_raw_parts(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let c = unsafe { BitSet::from_raw_parts(b.raw_blocks().to_vec(), b.bits_size()) };
+            assert_eq!(b, c);
+        }
+        true
+    }
 }
 
 #[cfg(test)]
-#[macro_use]
-mod test_operators {
+mod test_set_operations {
+    use super::test_helpers::{make_bitset, SIZES};
     use super::*;
 
-    /// Checks logical and function converting between bitsets of different sizes
-    macro_rules! check_logical_bit_and {
-        ($func:ident, $left:ty, $right:ty) => {
+    /// Checks the set operation against the operation on each bit.
+    macro_rules! check_set_operation {
+        ($func:ident, $operation:ident, $bit_operation:expr) => {
             #[quickcheck]
-            fn $func(left: $left, right: $right) -> bool {
-                let left_size = size_of::<$left>() * 8;
-                let right_size = size_of::<$right>() * 8;
-
-                let a = BitSet::from(left);
-                let b = BitSet::from(right);
-                let c = a & b;
-                assert_eq!(c.size, max(left_size, right_size));
-                assert_eq!(
-                    u128::try_from(c).unwrap(),
-                    u128::from(left as u128 & right as u128)
-                );
+            fn $func(left: u128, right: u128) -> bool {
+                for size in SIZES {
+                    let a = make_bitset(*size, left);
+                    let b = make_bitset(*size, right);
+                    let mut c = a.clone();
+                    c.$operation(&b);
+
+                    for i in 0..*size {
+                        assert_eq!(c.get(i), $bit_operation(a.get(i), b.get(i)));
+                    }
+                    // no bits set outside the size
+                    let mut d = c.clone();
+                    d.clear_unused_bits();
+                    assert_eq!(d, c);
+                }
                 true
             }
         };
     }
 
-    /// Checks logical and function converting between bitsets of different sizes
-    macro_rules! check_logical_bit_and_for_refs {
-        ($func:ident, $left:ty, $right:ty) => {
+    fn symmetric_difference_bit(left: bool, right: bool) -> bool {
+        left != right
+    }
+
+    check_set_operation!(check_union, union, |l, r| l || r);
+    check_set_operation!(check_intersect, intersect, |l, r| l && r);
+    check_set_operation!(check_difference, difference, |l: bool, r: bool| l && !r);
+    check_set_operation!(
+        check_symmetric_difference,
+        symmetric_difference,
+        symmetric_difference_bit
+    );
+
+    /// Checks the set operation for different sizes against the operation on each bit.
+    macro_rules! check_set_operation_extend {
+        ($func:ident, $operation:ident, $bit_operation:expr) => {
             #[quickcheck]
-            fn $func(left: $left, right: $right) -> bool {
-                let left_size = size_of::<$left>() * 8;
-                let right_size = size_of::<$right>() * 8;
+            fn $func(left: u128, right: u128) -> bool {
+                for (left_size, right_size) in &[(64, 70), (70, 64), (70, 70), (1, 200)] {
+                    let a = make_bitset(*left_size, left);
+                    let b = make_bitset(*right_size, right);
+                    let mut c = a.clone();
+                    c.$operation(&b);
+
+                    assert_eq!(c.size, max(*left_size, *right_size));
+                    assert_eq!(c.blocks.len(), BitSet::blocks_number(c.size));
+                    for i in 0..c.size {
+                        let l = i < a.size && a.get(i);
+                        let r = i < b.size && b.get(i);
+                        assert_eq!(c.get(i), $bit_operation(l, r));
+                    }
+                }
+                true
+            }
+        };
+    }
 
-                let a = BitSet::from(left);
-                let b = BitSet::from(right);
-                let c = &a & &b;
-                assert_eq!(c.size, max(left_size, right_size));
+    check_set_operation_extend!(check_union_extend, union_extend, |l, r| l || r);
+    check_set_operation_extend!(check_intersect_extend, intersect_extend, |l, r| l && r);
+    check_set_operation_extend!(
+        check_difference_extend,
+        difference_extend,
+        |l: bool, r: bool| l && !r
+    );
+    check_set_operation_extend!(
+        check_symmetric_difference_extend,
+        symmetric_difference_extend,
+        symmetric_difference_bit
+    );
+
+    /// Checks the set operation returning the error against the panicking one.
+    macro_rules! check_try_set_operation {
+        ($func:ident, $try_operation:ident, $operation:ident) => {
+            #[quickcheck]
+            fn $func(left: u128, right: u128) -> bool {
+                let a = make_bitset(70, left);
+                let b = make_bitset(70, right);
+                let mut c = a.clone();
+                let mut d = a.clone();
+                assert_eq!(c.$try_operation(&b), Ok(()));
+                d.$operation(&b);
+                assert_eq!(c, d);
+
+                // different sizes don't change anything
+                let mut c = a.clone();
                 assert_eq!(
-                    u128::try_from(c).unwrap(),
-                    u128::from(left as u128 & right as u128)
+                    c.$try_operation(&BitSet::new(71)),
+                    Err(SizeMismatch {
+                        left: 70,
+                        right: 71
+                    })
                 );
+                assert_eq!(c, a);
                 true
             }
         };
     }
 
-    check_logical_bit_and!(check_logical_bit_and_u8_u8, u8, u8);
-    check_logical_bit_and!(check_logical_bit_and_u8_u16, u8, u16);
-    check_logical_bit_and!(check_logical_bit_and_u8_u32, u8, u32);
-    check_logical_bit_and!(check_logical_bit_and_u8_u64, u8, u64);
-    check_logical_bit_and!(check_logical_bit_and_u8_u128, u8, u128);
-    check_logical_bit_and!(check_logical_bit_and_u8_usize, u8, usize);
+    check_try_set_operation!(check_try_union, try_union, union);
+    check_try_set_operation!(check_try_intersect, try_intersect, intersect);
+    check_try_set_operation!(check_try_difference, try_difference, difference);
+    check_try_set_operation!(
+        check_try_symmetric_difference,
+        try_symmetric_difference,
+        symmetric_difference
+    );
 
-    check_logical_bit_and!(check_logical_bit_and_u16_u8,    u16, u8);
-    check_logical_bit_and!(check_logical_bit_and_u16_u16,   u16, u16);
-    check_logical_bit_and!(check_logical_bit_and_u16_u32,   u16, u32);
-    check_logical_bit_and!(check_logical_bit_and_u16_u64,   u16, u64);
-    check_logical_bit_and!(check_logical_bit_and_u16_u128,  u16, u128);
-    check_logical_bit_and!(check_logical_bit_and_u16_usize, u16, usize);
+    #[test]
+    fn check_size_mismatch_error() {
+        let error = SizeMismatch {
+            left: 70,
+            right: 71,
+        };
+        assert_eq!(
+            error.to_string(),
+            "BitSets have different sizes: 70 and 71."
+        );
+
+        let error: Box<dyn std::error::Error> = Box::new(error);
+        assert!(error.to_string().contains("70"));
+        assert!(error.to_string().contains("71"));
+    }
 
-    check_logical_bit_and!(check_logical_bit_and_u32_u8,    u32, u8);
-    check_logical_bit_and!(check_logical_bit_and_u32_u16,   u32, u16);
-    check_logical_bit_and!(check_logical_bit_and_u32_u32,   u32, u32);
-    check_logical_bit_and!(check_logical_bit_and_u32_u64,   u32, u64);
-    check_logical_bit_and!(check_logical_bit_and_u32_u128,  u32, u128);
-    check_logical_bit_and!(check_logical_bit_and_u32_usize, u32, usize);
+    #[test]
+    fn check_size_mismatch_error_propagation() {
+        fn try_union_all(
+            a: &mut BitSet,
+            b: &BitSet,
+            c: &BitSet,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            a.try_union(b)?;
+            a.try_union(c)?;
+            Ok(())
+        }
+        let mut a = BitSet::new(10);
+        assert!(try_union_all(&mut a, &BitSet::new(10), &BitSet::new(10)).is_ok());
+        let error = try_union_all(&mut a, &BitSet::new(10), &BitSet::new(12)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "BitSets have different sizes: 10 and 12."
+        );
+    }
 
-    check_logical_bit_and!(check_logical_bit_and_u64_u8,    u64, u8);
-    check_logical_bit_and!(check_logical_bit_and_u64_u16,   u64, u16);
-    check_logical_bit_and!(check_logical_bit_and_u64_u32,   u64, u32);
-    check_logical_bit_and!(check_logical_bit_and_u64_u64,   u64, u64);
-    check_logical_bit_and!(check_logical_bit_and_u64_u128,  u64, u128);
-    check_logical_bit_and!(check_logical_bit_and_u64_usize, u64, usize);
+    #[quickcheck]
+    fn check_union_and_intersect_all(first: u128, second: u128, third: u128) -> bool {
+        for size in SIZES {
+            let sets = [
+                make_bitset(*size, first),
+                make_bitset(*size, second),
+                make_bitset(*size, third),
+            ];
+            let union = BitSet::union_all(&sets).unwrap();
+            let intersection = BitSet::intersect_all(&sets).unwrap();
+            for i in 0..*size {
+                assert_eq!(union.get(i), sets.iter().any(|set| set.get(i)));
+                assert_eq!(intersection.get(i), sets.iter().all(|set| set.get(i)));
+            }
 
-    check_logical_bit_and!(check_logical_bit_and_u128_u8,    u128, u8);
-    check_logical_bit_and!(check_logical_bit_and_u128_u16,   u128, u16);
-    check_logical_bit_and!(check_logical_bit_and_u128_u32,   u128, u32);
-    check_logical_bit_and!(check_logical_bit_and_u128_u64,   u128, u64);
-    check_logical_bit_and!(check_logical_bit_and_u128_u128,  u128, u128);
-    check_logical_bit_and!(check_logical_bit_and_u128_usize, u128, usize);
+            assert_eq!(BitSet::union_all(&sets[..1]), Some(sets[0].clone()));
+            assert_eq!(BitSet::intersect_all(&sets[..1]), Some(sets[0].clone()));
+        }
+        true
+    }
 
-    check_logical_bit_and!(check_logical_bit_and_usize_u8,    usize, u8);
-    check_logical_bit_and!(check_logical_bit_and_usize_u16,   usize, u16);
-    check_logical_bit_and!(check_logical_bit_and_usize_u32,   usize, u32);
-    check_logical_bit_and!(check_logical_bit_and_usize_u64,   usize, u64);
-    check_logical_bit_and!(check_logical_bit_and_usize_u128,  usize, u128);
-    check_logical_bit_and!(check_logical_bit_and_usize_usize, usize, usize);
+    #[test]
+    fn check_union_and_intersect_all_without_sets() {
+        assert_eq!(BitSet::union_all(&[]), None);
+        assert_eq!(BitSet::intersect_all(&[]), None);
+    }
 
+    #[test]
+    #[should_panic(expected = "BitSets have different sizes: 70 and 71.")]
+    fn check_union_all_with_different_sizes() {
+        BitSet::union_all(&[BitSet::new(70), BitSet::new(70), BitSet::new(71)]);
+    }
 
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u8_u8, u8, u8);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u8_u16, u8, u16);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u8_u32, u8, u32);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u8_u64, u8, u64);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u8_u128, u8, u128);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u8_usize, u8, usize);
+    #[test]
+    fn check_extend_operations_with_different_sizes() {
+        let mut a = BitSet::new(64);
+        a.set(3, true);
+        let mut b = BitSet::new(70);
+        b.set(3, true);
+        b.set(69, true);
+
+        let mut c = a.clone();
+        c.union_extend(&b);
+        assert_eq!(c, b);
+
+        // the smaller other bitset has zeros above its size
+        let mut c = b.clone();
+        c.intersect_extend(&a);
+        assert_eq!(c.size, 70);
+        assert_eq!(c.collect_ones(), vec![3]);
+
+        let mut c = b.clone();
+        c.difference_extend(&a);
+        assert_eq!(c.collect_ones(), vec![69]);
+
+        let mut c = a.clone();
+        c.symmetric_difference_extend(&b);
+        assert_eq!(c.size, 70);
+        assert_eq!(c.collect_ones(), vec![69]);
+    }
 
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u16_u8,    u16, u8);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u16_u16,   u16, u16);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u16_u32,   u16, u32);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u16_u64,   u16, u64);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u16_u128,  u16, u128);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u16_usize, u16, usize);
+    #[test]
+    fn check_union_extend_with_the_same_size() {
+        let mut a = make_bitset(70, 0xf0f0);
+        let b = make_bitset(70, 0x0ff0);
+        let mut c = a.clone();
+        a.union_extend(&b);
+        c.union(&b);
+        assert_eq!(a, c);
+    }
 
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u32_u8,    u32, u8);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u32_u16,   u32, u16);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u32_u32,   u32, u32);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u32_u64,   u32, u64);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u32_u128,  u32, u128);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u32_usize, u32, usize);
+    #[test]
+    #[should_panic(expected = "BitSets have different sizes: 70 and 71.")]
+    fn check_union_with_different_size() {
+        BitSet::new(70).union(&BitSet::new(71));
+    }
 
-check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u64_u8,    u64, u8);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u64_u16,   u64, u16);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u64_u32,   u64, u32);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u64_u64,   u64, u64);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u64_u128,  u64, u128);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u64_usize, u64, usize);
+    #[quickcheck]
+    fn check_and_not_matches_difference(left: u128, right: u128) -> bool {
+        let a = make_bitset(200, left);
+        let b = make_bitset(200, right);
+        let mut c = a.clone();
+        c.difference(&b);
+        assert_eq!(a.and_not_owned(&b), c);
+        true
+    }
 
-check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u128_u8,    u128, u8);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u128_u16,   u128, u16);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u128_u32,   u128, u32);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u128_u64,   u128, u64);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u128_u128,  u128, u128);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_u128_usize, u128, usize);
+    #[test]
+    #[should_panic(expected = "BitSets have different sizes: 70 and 71.")]
+    fn check_and_not_with_different_size() {
+        BitSet::new(70).and_not(&BitSet::new(71));
+    }
 
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_usize_u8,    usize, u8);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_usize_u16,   usize, u16);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_usize_u32,   usize, u32);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_usize_u64,   usize, u64);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_usize_u128,  usize, u128);
-    check_logical_bit_and_for_refs!(check_logical_bit_and_for_refs_usize_usize, usize, usize);
+    #[quickcheck]
+    fn check_and_not(left: u128, right: u128) -> bool {
+        for size in SIZES {
+            let a = make_bitset(*size, left);
+            let b = make_bitset(*size, right);
 
-    // Checks =& function converting between bitsets of different sizes
-    // macro_rules! check_bit_and_assign {
-    //     ($func:ident, $left:ty, $right:ty) => {
-    //         #[quickcheck]
-    //         fn $func(left: $left, right: $right) -> bool {
-    //             let left_size = size_of::<$left>() * 8;
-    //             let right_size = size_of::<$right>() * 8;
+            let c = a.and_not_owned(&b);
+            for i in 0..*size {
+                // the difference of the sets
+                assert_eq!(c.get(i), a.get(i) && !b.get(i));
+            }
 
-    //             let mut a = BitSet::from(left);
-    //             let b = BitSet::from(right);
-    //             let a &= b;
-    //             assert_eq!(a.size, max(left_size, right_size));
-    //             assert_eq!(
-    //                 u128::try_from(a).unwrap(),
-    //                 u128::from(left as u128 & right as u128)
-    //             );
-    //             true
-    //         }
-    //     };
-    // }
+            let mut d = a.clone();
+            d.and_not(&b);
+            assert_eq!(c, d);
+            assert_eq!(a.and_not_count(&b), d.count());
+        }
+        true
+    }
 
-    // check_bit_and_assign!(check_logical_bit_and_assign_u8_u8, u8, u8);
+    #[test]
+    #[should_panic(expected = "BitSets have different sizes: 70 and 71.")]
+    fn check_and_not_count_with_different_size() {
+        BitSet::new(70).and_not_count(&BitSet::new(71));
+    }
+}
+
+#[cfg(test)]
+mod test_iterators {
+    use super::test_helpers::{make_bitset, SIZES};
+    use super::*;
+
+    #[test]
+    fn check_iter_ones_and_zeros_functions() {
+        let mut b = BitSet::new(70);
+        b.set(0, true);
+        b.set(63, true);
+        b.set(64, true);
+        b.set(69, true);
+        assert_eq!(b.iter_ones().collect::<Vec<_>>(), vec![0, 63, 64, 69]);
+        assert_eq!(b.iter_zeros().count(), 66);
+        assert_eq!(b.iter_zeros().take(3).collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        b.set_all(true);
+        assert_eq!(b.iter_zeros().next(), None);
+    }
+
+    #[quickcheck]
+    fn check_iter_ones_and_zeros(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let ones: Vec<usize> = b.iter_ones().collect();
+            let zeros: Vec<usize> = b.iter_zeros().collect();
+
+            assert_eq!(ones, (0..*size).filter(|i| b.get(*i)).collect::<Vec<_>>());
+            assert_eq!(zeros, (0..*size).filter(|i| !b.get(*i)).collect::<Vec<_>>());
+
+            // together they cover all the positions exactly once
+            let mut all = [ones, zeros].concat();
+            all.sort_unstable();
+            assert_eq!(all, (0..*size).collect::<Vec<_>>());
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_for_each_set_bit(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let mut ones = vec![];
+            b.for_each_set_bit(|i| ones.push(i));
+            assert_eq!(ones, b.iter_ones().collect::<Vec<_>>());
+        }
+        true
+    }
+
+    #[test]
+    fn check_runs() {
+        let mut b = BitSet::new(200);
+        b.flip_range(3, 5);
+        b.flip_range(60, 130);
+        assert_eq!(
+            b.runs().collect::<Vec<_>>(),
+            vec![
+                (0, 3, false),
+                (3, 2, true),
+                (5, 55, false),
+                (60, 70, true),
+                (130, 70, false)
+            ]
+        );
+
+        let mut b = BitSet::new(70);
+        assert_eq!(b.runs().collect::<Vec<_>>(), vec![(0, 70, false)]);
+        b.negate();
+        assert_eq!(b.runs().collect::<Vec<_>>(), vec![(0, 70, true)]);
+    }
 
+    #[quickcheck]
+    fn check_runs_for_random_values(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let runs: Vec<(usize, usize, bool)> = b.runs().collect();
+
+            // no gaps and alternating values
+            let mut position = 0;
+            for (i, (start, length, value)) in runs.iter().enumerate() {
+                assert_eq!(*start, position);
+                assert!(*length > 0);
+                assert!((*start..start + length).all(|j| b.get(j) == *value));
+                if i > 0 {
+                    assert_ne!(runs[i - 1].2, *value);
+                }
+                position += length;
+            }
+            assert_eq!(position, *size);
+            assert_eq!(runs.len(), b.count_runs(true) + b.count_runs(false));
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_collect_ones(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let ones = b.collect_ones();
+            assert_eq!(ones.len(), b.count());
+            assert!(ones.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(ones.iter().all(|i| b.get(*i)));
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_iter_ones_reversed(value: u128) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let mut ones: Vec<usize> = b.iter_ones().collect();
+            ones.reverse();
+            assert_eq!(b.iter_ones().rev().collect::<Vec<_>>(), ones);
+
+            let mut zeros: Vec<usize> = b.iter_zeros().collect();
+            zeros.reverse();
+            assert_eq!(b.iter_zeros().rev().collect::<Vec<_>>(), zeros);
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn check_iter_ones_from_both_ends(value: u128, directions: u64) -> bool {
+        for size in SIZES {
+            let b = make_bitset(*size, value);
+            let mut iter = b.iter_ones();
+            let mut ones = vec![];
+            // the bits of the directions choose the end to take the next position from
+            for i in 0.. {
+                let next = if (directions >> (i % 64)) & 1 == 1 {
+                    iter.next_back()
+                } else {
+                    iter.next()
+                };
+                match next {
+                    Some(position) => ones.push(position),
+                    None => break,
+                }
+            }
+            ones.sort_unstable();
+            assert_eq!(ones, b.iter_ones().collect::<Vec<_>>());
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next_back(), None);
+        }
+        true
+    }
 }
@@ -10,4 +10,5 @@ extern crate quickcheck;
 extern crate quickcheck_macros;
 
 pub mod bitset;
+#[allow(dead_code)]
 mod traits;